@@ -0,0 +1,192 @@
+use std::{collections::BTreeMap, path::Path};
+
+use eyre::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+/// Version of the directory layout, increased when it changes incompatibly.
+pub const ARCHIVE_VERSION: u32 = 1;
+pub const MANIFEST_FILE_NAME: &str = "manifest.toml";
+pub const INDEX_FILE_NAME: &str = "index.md";
+
+/// The `manifest.toml` of an archived year, listing the files of every day
+/// with their checksums.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub year: u32,
+    #[serde(default, rename = "day", skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<ArchivedDay>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedDay {
+    pub day: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Stars of the account when the day was archived, a new star means the
+    /// puzzle gained a part
+    pub stars: u32,
+    /// SHA-256 of the files in the directory of the day
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Loads the manifest of the year directory, or starts an empty one.
+    pub async fn load(dir: &Path, year: u32) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE_NAME);
+        let Ok(content) = fs::read_to_string(&path).await else {
+            return Ok(Self {
+                version: ARCHIVE_VERSION,
+                year,
+                days: Vec::new(),
+            });
+        };
+        let manifest: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse '{}'", path.to_string_lossy()))?;
+        if manifest.version != ARCHIVE_VERSION {
+            bail!(
+                "The archive in '{}' has version {}, but only version {ARCHIVE_VERSION} is supported",
+                dir.to_string_lossy(),
+                manifest.version
+            );
+        }
+        if manifest.year != year {
+            bail!(
+                "The archive in '{}' is of {}",
+                dir.to_string_lossy(),
+                manifest.year
+            );
+        }
+        Ok(manifest)
+    }
+
+    pub async fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(MANIFEST_FILE_NAME);
+        fs::write(&path, toml::to_string_pretty(self)?)
+            .await
+            .with_context(|| format!("Couldn't write to '{}'", path.to_string_lossy()))
+    }
+
+    pub fn day(&self, day: u32) -> Option<&ArchivedDay> {
+        self.days.iter().find(|archived| archived.day == day)
+    }
+
+    /// Adds or replaces the entry of a day, keeping the days sorted.
+    pub fn insert(&mut self, archived: ArchivedDay) {
+        self.days.retain(|day| day.day != archived.day);
+        self.days.push(archived);
+        self.days.sort_by_key(|day| day.day);
+    }
+
+    /// Markdown page linking the puzzle and input of every archived day.
+    pub fn render_index(&self) -> String {
+        let mut index = format!("# Advent of Code {}\n\n", self.year);
+        for archived in &self.days {
+            let dir = day_dir_name(archived.day);
+            let name = match &archived.title {
+                Some(title) => format!("Day {}: {title}", archived.day),
+                None => format!("Day {}", archived.day),
+            };
+            let stars = "*".repeat(archived.stars as usize);
+            index.push_str(&format!("- [{name}](./{dir}/problem.md) {stars}\n"));
+            let mut links: Vec<_> = archived
+                .files
+                .keys()
+                .filter(|file| *file != "problem.md")
+                .map(|file| format!("[{file}](./{dir}/{file})"))
+                .collect();
+            links.sort_by_key(|link| !link.starts_with("[input"));
+            if !links.is_empty() {
+                index.push_str(&format!("  - {}\n", links.join(", ")));
+            }
+        }
+        index
+    }
+}
+
+impl ArchivedDay {
+    /// Names of the files in `dir` that are missing or don't match their checksum.
+    pub async fn damaged_files(&self, dir: &Path) -> Vec<String> {
+        let mut damaged = Vec::new();
+        for (file, expected) in &self.files {
+            match fs::read(dir.join(file)).await {
+                Ok(content) if checksum(&content) == *expected => {}
+                _ => damaged.push(file.clone()),
+            }
+        }
+        damaged
+    }
+}
+
+/// Directory of a day within the year, padded to keep them sorted.
+pub fn day_dir_name(day: u32) -> String {
+    format!("day_{day:02}")
+}
+
+/// Hex encoded SHA-256 of the content.
+pub fn checksum(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> Manifest {
+        let mut manifest = Manifest {
+            version: ARCHIVE_VERSION,
+            year: 2022,
+            days: Vec::new(),
+        };
+        for (day, title) in [(2, None), (1, Some("Calorie Counting".to_string()))] {
+            manifest.insert(ArchivedDay {
+                day,
+                title,
+                stars: day,
+                files: BTreeMap::from([
+                    ("example_0.txt".into(), checksum(b"1\n")),
+                    ("input.txt".into(), checksum(b"2\n")),
+                    ("problem.md".into(), checksum(b"3\n")),
+                ]),
+            });
+        }
+        manifest
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(
+            checksum(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let manifest = manifest();
+        let content = toml::to_string_pretty(&manifest).unwrap();
+        assert!(content.starts_with("version = 1\nyear = 2022\n"));
+        let parsed: Manifest = toml::from_str(&content).unwrap();
+        assert_eq!(parsed.days.len(), 2);
+        assert_eq!(parsed.day(2).unwrap().files, manifest.day(2).unwrap().files);
+    }
+
+    #[test]
+    fn test_render_index() {
+        assert_eq!(
+            manifest().render_index(),
+            "# Advent of Code 2022
+
+- [Day 1: Calorie Counting](./day_01/problem.md) *
+  - [input.txt](./day_01/input.txt), [example_0.txt](./day_01/example_0.txt)
+- [Day 2](./day_02/problem.md) **
+  - [input.txt](./day_02/input.txt), [example_0.txt](./day_02/example_0.txt)
+"
+        );
+    }
+}
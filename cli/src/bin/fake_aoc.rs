@@ -0,0 +1,372 @@
+//! Minimal stand-in for the Advent of Code website.
+//!
+//! Serves puzzle pages, inputs and canned submit responses from a fixtures
+//! directory, so the `aoc` CLI can be run end to end without network access.
+//! Every day lives in `<fixtures>/<year>/day_<day>/` and contains
+//! `part_1.html`, `part_2.html` (the `<article>` of each part), `input.txt`,
+//! `answer_1.txt` and `answer_2.txt`. Private leaderboards are read from
+//! `<fixtures>/<year>/leaderboard_<id>.json`, the personal times from
+//! `<fixtures>/<year>/personal_times.html`.
+//!
+//! Only built with the `fake-server` feature, e.g. `cargo test --features fake-server`.
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    io::{self, Write},
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use eyre::Result;
+use hyper::{
+    header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, StatusCode,
+};
+use tokio::fs;
+
+#[derive(Debug, Clone, Parser)]
+#[command()]
+struct Args {
+    #[arg(short, long, default_value = "./fixtures")]
+    fixtures: PathBuf,
+    #[arg(short, long, default_value_t = 0)]
+    port: u16,
+    /// Seconds a wrong answer blocks further submissions
+    #[arg(long, default_value_t = 60)]
+    lockout: u64,
+    /// Number of puzzle page requests answered with 404, as if the puzzle wasn't unlocked yet
+    #[arg(long, default_value_t = 0)]
+    locked_requests: u32,
+    /// Session cookie the settings page accepts as logged in
+    #[arg(long, default_value = "test-session")]
+    session: String,
+}
+
+struct Server {
+    fixtures: PathBuf,
+    lockout: Duration,
+    locked_requests: Mutex<u32>,
+    session: String,
+    days: Mutex<HashMap<(u32, u32), DayState>>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct DayState {
+    accepted_answers: Vec<String>,
+    locked_until: Option<Instant>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let server = Arc::new(Server {
+        fixtures: args.fixtures,
+        lockout: Duration::from_secs(args.lockout),
+        locked_requests: Mutex::new(args.locked_requests),
+        session: args.session,
+        days: Mutex::new(HashMap::new()),
+    });
+
+    let make_service = make_service_fn(move |_| {
+        let server = server.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let server = server.clone();
+                async move { Ok::<_, Infallible>(server.handle(request).await) }
+            }))
+        }
+    });
+
+    let address = SocketAddr::from(([127, 0, 0, 1], args.port));
+    let http_server = hyper::Server::try_bind(&address)?.serve(make_service);
+    println!("Listening on http://{}", http_server.local_addr());
+    io::stdout().flush()?;
+    http_server.await?;
+    Ok(())
+}
+
+impl Server {
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let path = request.uri().path().to_string();
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let session = session_cookie(&request);
+        let has_session = session.is_some();
+        let response = match (request.method(), segments.as_slice()) {
+            (&Method::GET, ["settings"]) => Some(self.settings(session.as_deref())),
+            (&Method::GET, [year]) => match year.parse() {
+                Ok(year) => Some(self.calendar(year).await),
+                Err(_) => None,
+            },
+            (&Method::GET, [year, "day", day]) => match parse_day(year, day) {
+                Some((year, day)) => self.puzzle_page(year, day).await,
+                None => None,
+            },
+            (&Method::GET, [year, "day", day, "input"]) => match parse_day(year, day) {
+                Some(_) if !has_session => Some(bad_request(
+                    "Puzzle inputs differ by user.  Please log in to get your puzzle input.",
+                )),
+                Some((year, day)) => self.input(year, day).await,
+                None => None,
+            },
+            (&Method::POST, [year, "day", day, "answer"]) => match parse_day(year, day) {
+                Some(_) if !has_session => Some(bad_request("Please log in to submit answers.")),
+                Some((year, day)) => {
+                    let body = hyper::body::to_bytes(request.into_body())
+                        .await
+                        .unwrap_or_default();
+                    self.answer(year, day, &body).await
+                }
+                None => None,
+            },
+            (&Method::GET, [year, "leaderboard", "self"]) => match year.parse::<u32>() {
+                Ok(_) if !has_session => None,
+                Ok(year) => self.personal_times(year).await,
+                Err(_) => None,
+            },
+            (&Method::GET, [year, "leaderboard", "private", "view", file]) => {
+                match (year.parse::<u32>(), file.strip_suffix(".json")) {
+                    (Ok(_), Some(_)) if !has_session => None,
+                    (Ok(year), Some(id)) => self.leaderboard(year, id).await,
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        response.unwrap_or_else(not_found)
+    }
+
+    /// Only logged in users see their name in the header of the settings page.
+    fn settings(&self, session: Option<&str>) -> Response<Body> {
+        if session != Some(self.session.as_str()) {
+            return html(
+                "<!DOCTYPE html>\n<html><body><main><p>To play, please identify yourself via \
+                 one of these services:</p></main></body></html>\n"
+                    .into(),
+            );
+        }
+        html(render_page(
+            "Settings - Advent of Code",
+            "<form method=\"post\" action=\"/settings\"></form>",
+        ))
+    }
+
+    async fn calendar(&self, year: u32) -> Response<Body> {
+        let mut days = Vec::new();
+        for day in 1..=25 {
+            let day_link = match fs::metadata(self.day_dir(year, day)).await {
+                Ok(_) => {
+                    let (class, label) = match self.state(year, day).accepted_answers.len() {
+                        0 => ("", ""),
+                        1 => (" calendar-complete", ", one star"),
+                        _ => (" calendar-verycomplete", ", two stars"),
+                    };
+                    format!(
+                        "<a aria-label=\"Day {day}{label}\" href=\"/{year}/day/{day}\" \
+                         class=\"calendar-day{day}{class}\">Day {day:>2}</a>"
+                    )
+                }
+                Err(_) => format!("<span aria-hidden=\"true\" class=\"calendar-day{day}\"></span>"),
+            };
+            days.push(day_link);
+        }
+        let calendar = format!("<pre class=\"calendar\">\n{}\n</pre>", days.join("\n"));
+        html(render_page(&format!("Advent of Code {year}"), &calendar))
+    }
+
+    async fn puzzle_page(&self, year: u32, day: u32) -> Option<Response<Body>> {
+        {
+            let mut locked_requests = self.locked_requests.lock().unwrap();
+            if *locked_requests > 0 {
+                *locked_requests -= 1;
+                return None;
+            }
+        }
+        let dir = self.day_dir(year, day);
+        let part_1 = fs::read_to_string(dir.join("part_1.html")).await.ok()?;
+        let accepted_answers = self.state(year, day).accepted_answers;
+
+        let mut body = part_1;
+        if let Some(answer) = accepted_answers.first() {
+            body.push_str(&format!(
+                "\n<p>Your puzzle answer was <code>{answer}</code>.</p>"
+            ));
+            if let Ok(part_2) = fs::read_to_string(dir.join("part_2.html")).await {
+                body.push('\n');
+                body.push_str(&part_2);
+            }
+        }
+        if let Some(answer) = accepted_answers.get(1) {
+            body.push_str(&format!(
+                "\n<p>Your puzzle answer was <code>{answer}</code>.</p>\
+                 \n<p class=\"day-success\">Both parts of this puzzle are complete! \
+                 They provide two gold stars: **</p>"
+            ));
+        }
+
+        Some(html(render_page(
+            &format!("Day {day} - Advent of Code {year}"),
+            &body,
+        )))
+    }
+
+    async fn input(&self, year: u32, day: u32) -> Option<Response<Body>> {
+        let input = fs::read_to_string(self.day_dir(year, day).join("input.txt"))
+            .await
+            .ok()?;
+        Some(Response::new(Body::from(input)))
+    }
+
+    async fn leaderboard(&self, year: u32, id: &str) -> Option<Response<Body>> {
+        let file = self
+            .fixtures
+            .join(year.to_string())
+            .join(format!("leaderboard_{id}.json"));
+        let json = fs::read_to_string(file).await.ok()?;
+        Some(
+            Response::builder()
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json))
+                .unwrap(),
+        )
+    }
+
+    async fn personal_times(&self, year: u32) -> Option<Response<Body>> {
+        let file = self
+            .fixtures
+            .join(year.to_string())
+            .join("personal_times.html");
+        let article = fs::read_to_string(file).await.ok()?;
+        Some(html(render_page(
+            &format!("Personal Times - Advent of Code {year}"),
+            &article,
+        )))
+    }
+
+    async fn answer(&self, year: u32, day: u32, body: &[u8]) -> Option<Response<Body>> {
+        let form: HashMap<String, String> =
+            url::form_urlencoded::parse(body).into_owned().collect();
+        let level: usize = form.get("level")?.parse().ok()?;
+        let answer = form.get("answer")?.trim().to_string();
+        let expected =
+            fs::read_to_string(self.day_dir(year, day).join(format!("answer_{level}.txt")))
+                .await
+                .ok()?
+                .trim()
+                .to_string();
+
+        let now = Instant::now();
+        let mut days = self.days.lock().unwrap();
+        let state = days.entry((year, day)).or_default();
+
+        let message = if let Some(wait) = state
+            .locked_until
+            .and_then(|until| until.checked_duration_since(now))
+        {
+            format!(
+                "You gave an answer too recently; you have to wait after submitting an answer \
+                 before trying again.  You have {} left to wait. \
+                 [<a href=\"/{year}/day/{day}\">Return to Day {day}</a>]",
+                format_wait(wait)
+            )
+        } else if level != state.accepted_answers.len() + 1 {
+            format!(
+                "You don't seem to be solving the right level.  Did you already complete it? \
+                 [<a href=\"/{year}/day/{day}\">Return to Day {day}</a>]"
+            )
+        } else if answer == expected {
+            state.accepted_answers.push(answer);
+            format!(
+                "That's the right answer!  You are one gold star closer to saving your vacation. \
+                 [<a href=\"/{year}/day/{day}#part2\">Continue to Part Two</a>]"
+            )
+        } else {
+            state.locked_until = Some(now + self.lockout);
+            let hint = match (answer.parse::<i64>(), expected.parse::<i64>()) {
+                (Ok(answer), Ok(expected)) if answer > expected => "; your answer is too high",
+                (Ok(answer), Ok(expected)) if answer < expected => "; your answer is too low",
+                _ => "",
+            };
+            format!(
+                "That's not the right answer{hint}.  If you're stuck, make sure you're using \
+                 the full input data. Please wait one minute before trying again. \
+                 [<a href=\"/{year}/day/{day}\">Return to Day {day}</a>]"
+            )
+        };
+
+        Some(html(render_page(
+            &format!("Day {day} - Advent of Code {year}"),
+            &format!("<article><p>{message}</p></article>"),
+        )))
+    }
+
+    fn state(&self, year: u32, day: u32) -> DayState {
+        let days = self.days.lock().unwrap();
+        days.get(&(year, day)).cloned().unwrap_or_default()
+    }
+
+    fn day_dir(&self, year: u32, day: u32) -> PathBuf {
+        self.fixtures
+            .join(year.to_string())
+            .join(format!("day_{day}"))
+    }
+}
+
+fn parse_day(year: &str, day: &str) -> Option<(u32, u32)> {
+    Some((year.parse().ok()?, day.parse().ok()?))
+}
+
+fn session_cookie(request: &Request<Body>) -> Option<String> {
+    request
+        .headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().strip_prefix("session="))
+        .find(|session| !session.is_empty())
+        .map(ToString::to_string)
+}
+
+fn format_wait(wait: Duration) -> String {
+    let seconds = wait.as_secs() + 1;
+    match (seconds / 60, seconds % 60) {
+        (0, seconds) => format!("{seconds}s"),
+        (minutes, seconds) => format!("{minutes}m {seconds}s"),
+    }
+}
+
+fn render_page(title: &str, main: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en-us\">\n<head>\n<meta charset=\"utf-8\"/>\n\
+         <title>{title}</title>\n</head>\n<body>\n\
+         <header><h1 class=\"title-global\"><a href=\"/\">Advent of Code</a></h1>\
+         <div class=\"user\">Fake User <span class=\"star-count\">4*</span></div></header>\n\
+         <main>\n{main}\n</main>\n</body>\n</html>\n"
+    )
+}
+
+fn html(page: String) -> Response<Body> {
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/html")
+        .body(Body::from(page))
+        .unwrap()
+}
+
+fn bad_request(message: &str) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .body(Body::from(message.to_string()))
+        .unwrap()
+}
+
+fn not_found() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("404 Not Found"))
+        .unwrap()
+}
@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use eyre::Result;
+
+/// Puzzles unlock at midnight US Eastern, which is always EST (UTC-5) in December.
+const UNLOCK_HOUR_UTC: u32 = 5;
+
+/// When the puzzle of the day unlocks, `None` for days outside of 1 to 25.
+pub fn unlock_time(year: u32, day: u32) -> Option<DateTime<Utc>> {
+    if !(1..=25).contains(&day) {
+        return None;
+    }
+    Utc.with_ymd_and_hms(year as i32, 12, day, UNLOCK_HOUR_UTC, 0, 0)
+        .single()
+}
+
+/// Number of puzzles of the year that are unlocked at `now`.
+pub fn unlocked_days(year: u32, now: DateTime<Utc>) -> u32 {
+    (1..=25)
+        .take_while(|day| unlock_time(year, *day).is_some_and(|unlock| unlock <= now))
+        .count() as u32
+}
+
+/// The first puzzle of the year that is still locked at `now`.
+pub fn next_unlock_day(year: u32, now: DateTime<Utc>) -> Option<u32> {
+    Some(unlocked_days(year, now) + 1).filter(|day| *day <= 25)
+}
+
+/// Formats the time it took to solve a puzzle, like `01:02:03` or `3d 01:02:03`.
+pub fn format_solve_time(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    let (days, hours) = (seconds / 86400, seconds % 86400 / 3600);
+    let (minutes, seconds) = (seconds % 3600 / 60, seconds % 60);
+    match days {
+        0 => format!("{hours:02}:{minutes:02}:{seconds:02}"),
+        days => format!("{days}d {hours:02}:{minutes:02}:{seconds:02}"),
+    }
+}
+
+/// Parses the stars (0, 1 or 2) of every unlocked day from the calendar page of a year.
+pub fn parse_calendar_stars(html: &str) -> Result<BTreeMap<u32, u32>> {
+    let dom = tl::parse(html, Default::default())?;
+    let parser = dom.parser();
+    let mut stars = BTreeMap::new();
+    for node in dom.query_selector("a").unwrap() {
+        let Some(tag) = node.get(parser).and_then(|node| node.as_tag()) else {
+            continue;
+        };
+        let Some(classes) = tag.attributes().class_iter() else {
+            continue;
+        };
+        let classes: Vec<_> = classes.collect();
+        let day = classes
+            .iter()
+            .find_map(|class| class.strip_prefix("calendar-day")?.parse().ok());
+        if let Some(day) = day {
+            let day_stars = match () {
+                _ if classes.contains(&"calendar-verycomplete") => 2,
+                _ if classes.contains(&"calendar-complete") => 1,
+                _ => 0,
+            };
+            stars.insert(day, day_stars);
+        }
+    }
+    Ok(stars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_calendar_stars() {
+        let html = r#"<pre class="calendar">
+<a aria-label="Day 1, two stars" href="/2022/day/1" class="calendar-day1 calendar-verycomplete">..</a>
+<a aria-label="Day 2, one star" href="/2022/day/2" class="calendar-day2 calendar-complete">..</a>
+<a aria-label="Day 3" href="/2022/day/3" class="calendar-day3">..</a>
+<span aria-hidden="true" class="calendar-day4"></span>
+<a href="/2022/about">About</a>
+</pre>"#;
+        let stars = parse_calendar_stars(html).unwrap();
+        assert_eq!(stars, BTreeMap::from([(1, 2), (2, 1), (3, 0)]));
+    }
+
+    #[test]
+    fn test_unlocked_days() {
+        let now = Utc.with_ymd_and_hms(2022, 12, 3, 4, 59, 59).unwrap();
+        assert_eq!(unlocked_days(2022, now), 2);
+        assert_eq!(next_unlock_day(2022, now), Some(3));
+        assert_eq!(unlocked_days(2022, now + Duration::seconds(1)), 3);
+        assert_eq!(unlocked_days(2021, now), 25);
+        assert_eq!(next_unlock_day(2021, now), None);
+        assert_eq!(unlocked_days(2023, now), 0);
+        assert_eq!(unlock_time(2022, 0), None);
+        assert_eq!(unlock_time(2022, 26), None);
+    }
+
+    #[test]
+    fn test_format_solve_time() {
+        assert_eq!(format_solve_time(Duration::seconds(3723)), "01:02:03");
+        assert_eq!(
+            format_solve_time(Duration::seconds(3 * 86400 + 5)),
+            "3d 00:00:05"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, future::Future, sync::Mutex};
+
+use eyre::{bail, ContextCompat, Result};
+
+use crate::{
+    examples::{extract_examples, ExampleCandidate},
+    http::{AocHttp, CachePolicy},
+    markdown::format_html_output,
+    submit::SubmitOutcome,
+};
+
+/// Message of the server to a submitted answer and how it was classified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmitResponse {
+    /// The `<article>` of the response as Markdown
+    pub message: String,
+    pub outcome: SubmitOutcome,
+}
+
+/// Access to the puzzles of one Advent of Code account.
+pub trait AocClient: Sync {
+    /// HTML of the puzzle page, containing the parts that are unlocked.
+    fn puzzle(&self, year: u32, day: u32) -> impl Future<Output = Result<String>> + Send;
+
+    fn input(&self, year: u32, day: u32) -> impl Future<Output = Result<String>> + Send;
+
+    /// The `<pre>` blocks of the puzzle page that might be the example input.
+    fn examples(
+        &self,
+        year: u32,
+        day: u32,
+    ) -> impl Future<Output = Result<Vec<ExampleCandidate>>> + Send {
+        async move { extract_examples(&self.puzzle(year, day).await?) }
+    }
+
+    fn submit(
+        &self,
+        year: u32,
+        day: u32,
+        level: u32,
+        answer: &str,
+    ) -> impl Future<Output = Result<SubmitResponse>> + Send;
+}
+
+/// Client of the Advent of Code website, or a server imitating it.
+pub struct HttpClient {
+    http: AocHttp,
+    base_url: String,
+}
+
+impl HttpClient {
+    pub fn new(http: AocHttp, base_url: &str) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The underlying client, for pages the trait doesn't cover.
+    pub fn http(&self) -> &AocHttp {
+        &self.http
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn puzzle_url(&self, year: u32, day: u32) -> String {
+        format!("{}/{year}/day/{day}", self.base_url)
+    }
+}
+
+impl AocClient for HttpClient {
+    async fn puzzle(&self, year: u32, day: u32) -> Result<String> {
+        let url = self.puzzle_url(year, day);
+        self.http.get_text(&url, CachePolicy::Keep).await
+    }
+
+    async fn input(&self, year: u32, day: u32) -> Result<String> {
+        let url = format!("{}/input", self.puzzle_url(year, day));
+        // Inputs never change, so they are cached for good
+        self.http.get_text(&url, CachePolicy::Keep).await
+    }
+
+    async fn submit(
+        &self,
+        year: u32,
+        day: u32,
+        level: u32,
+        answer: &str,
+    ) -> Result<SubmitResponse> {
+        let url = format!("{}/answer", self.puzzle_url(year, day));
+        let level = level.to_string();
+        let response = self
+            .http
+            .post_form(&url, &[("level", &level), ("answer", answer)])
+            .await?;
+        let message = format_html_output(&response)?;
+        let Some(outcome) = SubmitOutcome::from_response(&message) else {
+            bail!("Unrecognized response:\n{message}");
+        };
+        Ok(SubmitResponse { message, outcome })
+    }
+}
+
+/// In-memory stand-in for the website, e.g. for tests of code using an [`AocClient`].
+#[derive(Debug, Default)]
+pub struct FakeClient {
+    days: Mutex<HashMap<(u32, u32), FakeDay>>,
+}
+
+/// A puzzle of the [`FakeClient`] with the answers it accepts.
+#[derive(Debug, Clone, Default)]
+pub struct FakeDay {
+    pub puzzle: String,
+    pub input: String,
+    pub answers: Vec<String>,
+    /// Number of solved levels
+    pub solved: usize,
+}
+
+impl FakeClient {
+    pub fn with_day(self, year: u32, day: u32, fake_day: FakeDay) -> Self {
+        self.days.lock().unwrap().insert((year, day), fake_day);
+        self
+    }
+
+    fn day(&self, year: u32, day: u32) -> Result<FakeDay> {
+        self.days
+            .lock()
+            .unwrap()
+            .get(&(year, day))
+            .cloned()
+            .with_context(|| format!("Day {day} of {year} doesn't exist"))
+    }
+}
+
+impl AocClient for FakeClient {
+    async fn puzzle(&self, year: u32, day: u32) -> Result<String> {
+        Ok(self.day(year, day)?.puzzle)
+    }
+
+    async fn input(&self, year: u32, day: u32) -> Result<String> {
+        Ok(self.day(year, day)?.input)
+    }
+
+    async fn submit(
+        &self,
+        year: u32,
+        day: u32,
+        level: u32,
+        answer: &str,
+    ) -> Result<SubmitResponse> {
+        let mut days = self.days.lock().unwrap();
+        let fake_day = days
+            .get_mut(&(year, day))
+            .with_context(|| format!("Day {day} of {year} doesn't exist"))?;
+        let expected = (level as usize)
+            .checked_sub(1)
+            .and_then(|index| fake_day.answers.get(index));
+        let outcome = match expected {
+            _ if level as usize != fake_day.solved + 1 => SubmitOutcome::WrongLevel,
+            Some(expected) if expected == answer => {
+                fake_day.solved += 1;
+                SubmitOutcome::Correct
+            }
+            Some(expected) => match (expected.parse::<i64>(), answer.parse::<i64>()) {
+                (Ok(expected), Ok(answer)) if answer > expected => SubmitOutcome::TooHigh,
+                (Ok(expected), Ok(answer)) if answer < expected => SubmitOutcome::TooLow,
+                _ => SubmitOutcome::Wrong,
+            },
+            None => SubmitOutcome::WrongLevel,
+        };
+        Ok(SubmitResponse {
+            message: outcome.to_string(),
+            outcome,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> FakeClient {
+        FakeClient::default().with_day(
+            2022,
+            1,
+            FakeDay {
+                puzzle: "<p>For example:</p><pre><code>1\n2\n</code></pre>".into(),
+                input: "1\n2\n3\n".into(),
+                answers: vec!["24000".into(), "45000".into()],
+                solved: 0,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fake_client_submit() {
+        let client = client();
+        let outcome = |level, answer: &'static str| {
+            let client = &client;
+            async move { client.submit(2022, 1, level, answer).await.unwrap().outcome }
+        };
+        assert_eq!(outcome(0, "24000").await, SubmitOutcome::WrongLevel);
+        assert_eq!(outcome(2, "45000").await, SubmitOutcome::WrongLevel);
+        assert_eq!(outcome(1, "30000").await, SubmitOutcome::TooHigh);
+        assert_eq!(outcome(1, "100").await, SubmitOutcome::TooLow);
+        assert_eq!(outcome(1, "abc").await, SubmitOutcome::Wrong);
+        assert_eq!(outcome(1, "24000").await, SubmitOutcome::Correct);
+        assert_eq!(outcome(1, "24000").await, SubmitOutcome::WrongLevel);
+        assert_eq!(outcome(2, "45000").await, SubmitOutcome::Correct);
+        assert!(client.submit(2022, 2, 1, "1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fake_client_examples() {
+        let client = client();
+        assert_eq!(client.input(2022, 1).await.unwrap(), "1\n2\n3\n");
+        let examples = client.examples(2022, 1).await.unwrap();
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].text, "1\n2\n");
+    }
+}
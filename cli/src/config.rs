@@ -0,0 +1,134 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result};
+use tokio::fs;
+
+use crate::model::Config;
+
+pub const CONFIG_FILE_NAME: &str = "aoc.toml";
+
+/// The merged config and the files it was loaded from.
+#[derive(Debug, Clone, Default)]
+pub struct LoadedConfig {
+    pub config: Config,
+    /// Loaded files, from the outermost to the innermost
+    pub files: Vec<PathBuf>,
+    /// The innermost file that defines a day, or the explicitly given one
+    pub day_file: Option<PathBuf>,
+}
+
+impl LoadedConfig {
+    /// Loads every `aoc.toml` from the current directory upwards.
+    ///
+    /// An explicit config is used as the day file and the search starts at its
+    /// directory instead.
+    pub async fn discover(explicit: Option<&Path>) -> Result<Self> {
+        let explicit = explicit.map(absolute).transpose()?;
+        let mut paths: Vec<_> = explicit.iter().cloned().collect();
+        let start = match &explicit {
+            Some(explicit) => explicit.parent().map(Path::to_path_buf),
+            None => Some(env::current_dir()?),
+        };
+        let mut dir = start.as_deref();
+        while let Some(current) = dir {
+            let path = current.join(CONFIG_FILE_NAME);
+            if !paths.contains(&path) && fs::metadata(&path).await.is_ok() {
+                paths.push(path);
+            }
+            dir = current.parent();
+        }
+
+        let mut loaded = Self::default();
+        for path in paths.into_iter().rev() {
+            let config = load_file(&path).await?;
+            if config.day.is_some() || explicit.as_ref() == Some(&path) {
+                loaded.day_file = Some(path.clone());
+            }
+            loaded.config = loaded.config.merge(config);
+            loaded.files.push(path);
+        }
+        Ok(loaded)
+    }
+}
+
+impl Config {
+    /// Merges a config further down the tree into this one, values of `other` win.
+    pub fn merge(self, other: Config) -> Config {
+        Config {
+            year: other.year.or(self.year),
+            day: other.day.or(self.day),
+            base_url: other.base_url.or(self.base_url),
+            template: other.template.or(self.template),
+            lang: other.lang.or(self.lang),
+            session_file: other.session_file.or(self.session_file),
+            profile: other.profile.or(self.profile),
+            profiles: self.profiles.into_iter().chain(other.profiles).collect(),
+            example_index: other.example_index.or(self.example_index),
+            examples: match other.examples.is_empty() {
+                true => self.examples,
+                false => other.examples,
+            },
+        }
+    }
+}
+
+/// Parses a config file and makes its paths relative to the current directory.
+async fn load_file(path: &Path) -> Result<Config> {
+    let path_str = path.to_string_lossy();
+    let content = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Couldn't read '{path_str}'"))?;
+    let mut config: Config =
+        toml::from_str(&content).with_context(|| format!("Failed to parse '{path_str}'"))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    config.template = config.template.map(|template| dir.join(template));
+    config.session_file = config.session_file.map(|file| dir.join(file));
+    for profile in config.profiles.values_mut() {
+        profile.session_file = dir.join(&profile.session_file);
+    }
+    Ok(config)
+}
+
+fn absolute(path: &Path) -> Result<PathBuf> {
+    Ok(match path.is_absolute() {
+        true => path.to_path_buf(),
+        false => env::current_dir()?.join(path),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let repo: Config = toml::from_str(
+            r#"
+year = 2022
+template = "./rust/template"
+lang = "rust"
+"#,
+        )
+        .unwrap();
+        let day: Config = toml::from_str(
+            r#"
+year = 2021
+day = 3
+
+[[examples]]
+level = 1
+expected = "7"
+"#,
+        )
+        .unwrap();
+        let merged = repo.merge(day);
+        assert_eq!(merged.year, Some(2021));
+        assert_eq!(merged.day, Some(3));
+        assert_eq!(merged.template, Some("./rust/template".into()));
+        assert_eq!(merged.lang.as_deref(), Some("rust"));
+        assert_eq!(merged.examples.len(), 1);
+    }
+}
@@ -0,0 +1,142 @@
+use eyre::Result;
+
+/// A `<pre>` block of the puzzle page that might be the example input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExampleCandidate {
+    pub text: String,
+    /// Whether the paragraph before the block introduces an example
+    pub follows_for_example: bool,
+}
+
+pub fn extract_examples(html: &str) -> Result<Vec<ExampleCandidate>> {
+    let dom = tl::parse(html, Default::default())?;
+    let parser = dom.parser();
+    let mut candidates = Vec::new();
+    let mut last_paragraph = String::new();
+    for node in dom.nodes() {
+        let Some(tag) = node.as_tag() else { continue };
+        match tag.name().as_utf8_str().as_ref() {
+            "p" => last_paragraph = node.inner_text(parser).to_lowercase(),
+            "pre" => {
+                candidates.push(ExampleCandidate {
+                    text: decode_html_entities(&node.inner_text(parser)),
+                    follows_for_example: last_paragraph.contains("for example"),
+                });
+                last_paragraph.clear();
+            }
+            _ => {}
+        }
+    }
+    Ok(candidates)
+}
+
+/// Guesses which candidate is the example input.
+///
+/// Prefers multi-line blocks introduced by "For example", then any multi-line
+/// block, then the longest one.
+pub fn rank_examples(candidates: &[ExampleCandidate]) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .max_by_key(|(i, candidate)| {
+            let is_multiline = candidate.text.trim().contains('\n');
+            (
+                is_multiline && candidate.follows_for_example,
+                is_multiline,
+                candidate.text.len(),
+                // Prefer the first block on ties
+                usize::MAX - i,
+            )
+        })
+        .map(|(i, _)| i)
+}
+
+/// Candidates of `new` whose text isn't among `old`, e.g. the examples added
+/// by Part Two.
+pub fn new_examples(old: &[ExampleCandidate], new: &[ExampleCandidate]) -> Vec<ExampleCandidate> {
+    new.iter()
+        .filter(|candidate| !old.iter().any(|old| old.text == candidate.text))
+        .cloned()
+        .collect()
+}
+
+/// Decodes the entities `tl` keeps in the text of nodes.
+pub fn decode_html_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = r#"
+        <article>
+            <p>The map looks like <code>a-&gt;b</code>:</p>
+            <pre><code>a-&gt;b</code></pre>
+            <p>For example, consider the following input:</p>
+            <pre><code>1
+2
+3
+</code></pre>
+            <p>After the first step it looks like this:</p>
+            <pre><code>1
+2
+3
+4
+5
+</code></pre>
+        </article>
+    "#;
+
+    #[test]
+    fn test_extract_examples() {
+        let candidates = extract_examples(HTML).unwrap();
+        assert_eq!(
+            candidates,
+            vec![
+                ExampleCandidate {
+                    text: "a->b".into(),
+                    follows_for_example: false
+                },
+                ExampleCandidate {
+                    text: "1\n2\n3\n".into(),
+                    follows_for_example: true
+                },
+                ExampleCandidate {
+                    text: "1\n2\n3\n4\n5\n".into(),
+                    follows_for_example: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rank_examples() {
+        let candidates = extract_examples(HTML).unwrap();
+        assert_eq!(rank_examples(&candidates), Some(1));
+        assert_eq!(rank_examples(&candidates[2..]), Some(0));
+        assert_eq!(rank_examples(&[]), None);
+    }
+
+    #[test]
+    fn test_new_examples() {
+        let candidates = extract_examples(HTML).unwrap();
+        let part_two = extract_examples(&format!(
+            "{HTML}<article><p>For example:</p><pre><code>1\n2\n3\n</code></pre>\
+             <pre><code>4\n5\n</code></pre></article>"
+        ))
+        .unwrap();
+        assert_eq!(
+            new_examples(&candidates, &part_two),
+            vec![ExampleCandidate {
+                text: "4\n5\n".into(),
+                follows_for_example: false
+            }]
+        );
+        assert!(new_examples(&part_two, &candidates).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeZone, Utc};
+use eyre::{eyre, Context, Result};
+use reqwest::{
+    header::{HeaderValue, COOKIE},
+    Method, RequestBuilder,
+};
+use serde::de::DeserializeOwned;
+use tokio::{fs, sync::Mutex};
+
+/// Minimum time between two requests to the server, shared by all `aoc` processes.
+pub const DEFAULT_THROTTLE: Duration = Duration::from_secs(1);
+
+/// How a GET request uses the response cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Use the cached response if there is one, otherwise fetch and cache it
+    Keep,
+    /// Always fetch and cache the response
+    Refresh,
+    /// Always fetch and don't touch the cache
+    Bypass,
+}
+
+/// HTTP client for the Advent of Code server.
+///
+/// GET responses are cached on disk keyed by their URL and requests that hit
+/// the network are throttled to at most one per `throttle`.
+pub struct AocHttp {
+    client: reqwest::Client,
+    /// The session cookie, or why it couldn't be loaded. Only requests that hit
+    /// the network need it, so offline commands work without one.
+    session: std::result::Result<HeaderValue, String>,
+    cache_dir: PathBuf,
+    throttle: Duration,
+    /// Serializes the throttle of concurrent requests of this process
+    throttle_lock: Mutex<()>,
+    /// Ignore cached responses, as if every request used [`CachePolicy::Refresh`]
+    refresh: bool,
+}
+
+impl AocHttp {
+    pub fn new(
+        session: Result<String>,
+        user_agent: &str,
+        cache_dir: PathBuf,
+        throttle: Duration,
+        refresh: bool,
+    ) -> Result<Self> {
+        let session = session
+            .and_then(|session| {
+                let mut cookie = HeaderValue::from_str(&format!("session={session}"))
+                    .map_err(|_| eyre!("The session cookie contains invalid characters"))?;
+                // Keeps the cookie out of debug output
+                cookie.set_sensitive(true);
+                Ok(cookie)
+            })
+            .map_err(|err| format!("{err:#}"));
+        let client = reqwest::Client::builder().user_agent(user_agent).build()?;
+        Ok(Self {
+            client,
+            session,
+            cache_dir,
+            throttle,
+            throttle_lock: Mutex::new(()),
+            refresh,
+        })
+    }
+
+    pub async fn get_text(&self, url: &str, policy: CachePolicy) -> Result<String> {
+        let cache_path = self.cache_path(url);
+        if policy == CachePolicy::Keep && !self.refresh {
+            if let Ok(cached) = fs::read_to_string(&cache_path).await {
+                return Ok(cached);
+            }
+        }
+        self.wait_for_throttle().await?;
+        let text = self
+            .request(Method::GET, url)?
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        if policy != CachePolicy::Bypass {
+            fs::create_dir_all(&self.cache_dir).await?;
+            fs::write(&cache_path, &text)
+                .await
+                .with_context(|| format!("Couldn't write to '{}'", cache_path.to_string_lossy()))?;
+        }
+        Ok(text)
+    }
+
+    /// Fetches JSON without caching it, callers keep their own copy.
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.wait_for_throttle().await?;
+        let value = self
+            .request(Method::GET, url)?
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(value)
+    }
+
+    pub async fn post_form(&self, url: &str, form: &[(&str, &str)]) -> Result<String> {
+        self.wait_for_throttle().await?;
+        let text = self
+            .request(Method::POST, url)?
+            .form(form)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        Ok(text)
+    }
+
+    /// Removes the cached response of `url`, the next request fetches it again.
+    pub async fn invalidate(&self, url: &str) -> Result<()> {
+        match fs::remove_file(self.cache_path(url)).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn request(&self, method: Method, url: &str) -> Result<RequestBuilder> {
+        let session = self.session.clone().map_err(|err| eyre!(err))?;
+        Ok(self.client.request(method, url).header(COOKIE, session))
+    }
+
+    fn cache_path(&self, url: &str) -> PathBuf {
+        self.cache_dir.join(cache_key(url))
+    }
+
+    /// Sleeps until `throttle` has passed since the last request of any `aoc`
+    /// process, which is tracked in a file of the cache directory.
+    async fn wait_for_throttle(&self) -> Result<()> {
+        if self.throttle.is_zero() {
+            return Ok(());
+        }
+        let _lock = self.throttle_lock.lock().await;
+        let path = self.cache_dir.join("last_request");
+        let throttle = chrono::Duration::from_std(self.throttle)?;
+        if let Some(last_request) = read_timestamp(&path).await {
+            let wait = last_request + throttle - Utc::now();
+            if let Ok(wait) = wait.to_std() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+        fs::create_dir_all(&self.cache_dir).await?;
+        fs::write(&path, Utc::now().timestamp_millis().to_string()).await?;
+        Ok(())
+    }
+}
+
+async fn read_timestamp(path: &Path) -> Option<DateTime<Utc>> {
+    let content = fs::read_to_string(path).await.ok()?;
+    let millis = content.trim().parse().ok()?;
+    Utc.timestamp_millis_opt(millis).single()
+}
+
+/// Turns a URL into a file name, e.g. `adventofcode.com_2022_day_1_input`.
+fn cache_key(url: &str) -> String {
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    url.chars()
+        .map(|char| match char {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => char,
+            _ => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key() {
+        assert_eq!(
+            cache_key("https://adventofcode.com/2022/day/1/input"),
+            "adventofcode.com_2022_day_1_input"
+        );
+        assert_eq!(
+            cache_key("http://127.0.0.1:8080/2022/day/1"),
+            "127.0.0.1_8080_2022_day_1"
+        );
+    }
+}
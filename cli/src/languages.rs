@@ -0,0 +1,176 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use eyre::{bail, Context, Result};
+use serde::Deserialize;
+use tokio::fs;
+
+use crate::template::TemplateVars;
+
+/// File in the config directory to register extra languages or override the built-in ones.
+pub const LANGUAGES_FILE_NAME: &str = "languages.toml";
+
+const GO_MAIN: &str = include_str!("../templates/go/main.go");
+
+/// How a new day of a language is created.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Language {
+    /// Directory copied to the new day, without one the day starts empty
+    pub template: Option<PathBuf>,
+    /// Steps run after the template was copied
+    #[serde(default)]
+    pub steps: Vec<PostCopyStep>,
+}
+
+/// A step after copying the template, all strings may contain placeholders.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PostCopyStep {
+    /// Replaces text in a file, files that don't exist are skipped
+    Replace {
+        file: PathBuf,
+        from: String,
+        to: String,
+    },
+    /// Writes a file, creating its directory
+    Write { file: PathBuf, content: String },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct LanguagesFile {
+    #[serde(default)]
+    language: BTreeMap<String, Language>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LanguageRegistry {
+    languages: BTreeMap<String, Language>,
+}
+
+impl LanguageRegistry {
+    /// The built-in languages, extended by the `[language.<name>]` tables of `path`.
+    ///
+    /// Templates of the file are relative to it, like the ones of `aoc.toml`.
+    pub async fn load(path: &Path) -> Result<Self> {
+        let mut registry = Self::builtin();
+        if let Ok(content) = fs::read_to_string(path).await {
+            let mut file: LanguagesFile = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse '{}'", path.to_string_lossy()))?;
+            let dir = path.parent().unwrap_or_else(|| Path::new("."));
+            for language in file.language.values_mut() {
+                language.template = language.template.take().map(|template| dir.join(template));
+            }
+            registry.languages.extend(file.language);
+        }
+        Ok(registry)
+    }
+
+    fn builtin() -> Self {
+        let rust = Language {
+            template: Some("./template".into()),
+            steps: vec![PostCopyStep::Replace {
+                file: "Cargo.toml".into(),
+                from: r#"name = "template""#.into(),
+                to: r#"name = "day_{{day}}""#.into(),
+            }],
+        };
+        let go = Language {
+            template: None,
+            steps: (1..=2)
+                .map(|level| PostCopyStep::Write {
+                    file: format!("problem_{level}/main.go").into(),
+                    content: GO_MAIN.into(),
+                })
+                .collect(),
+        };
+        Self {
+            languages: BTreeMap::from([("rust".into(), rust), ("go".into(), go)]),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Language> {
+        match self.languages.get(name) {
+            Some(language) => Ok(language),
+            None => {
+                let known: Vec<_> = self.languages.keys().map(String::as_str).collect();
+                bail!("Unknown language '{name}', known are {}", known.join(", "))
+            }
+        }
+    }
+}
+
+impl PostCopyStep {
+    pub async fn run(&self, output: &Path, vars: &TemplateVars) -> Result<()> {
+        match self {
+            Self::Replace { file, from, to } => {
+                let path = output.join(vars.render(&file.to_string_lossy()));
+                let Ok(content) = fs::read_to_string(&path).await else {
+                    return Ok(());
+                };
+                let path_str = path.to_string_lossy();
+                println!("Update '{path_str}'");
+                fs::write(&path, content.replace(from, &vars.render(to)))
+                    .await
+                    .with_context(|| format!("Failed to update '{path_str}'"))?;
+            }
+            Self::Write { file, content } => {
+                let path = output.join(vars.render(&file.to_string_lossy()));
+                let path_str = path.to_string_lossy();
+                println!("Generate '{path_str}'");
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).await?;
+                }
+                fs::write(&path, vars.render(content))
+                    .await
+                    .with_context(|| format!("Failed to write '{path_str}'"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_languages_file() {
+        let file: LanguagesFile = toml::from_str(
+            r##"
+[language.python]
+template = "./py-template"
+
+[[language.python.steps]]
+action = "write"
+file = "solution_{{day}}.py"
+content = "# {{title}}"
+
+[[language.python.steps]]
+action = "replace"
+file = "pyproject.toml"
+from = "template"
+to = "day-{{day}}"
+"##,
+        )
+        .unwrap();
+        let python = &file.language["python"];
+        assert_eq!(python.template, Some("./py-template".into()));
+        assert!(matches!(
+            &python.steps[..],
+            [PostCopyStep::Write { .. }, PostCopyStep::Replace { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_unknown_language() {
+        let registry = LanguageRegistry::builtin();
+        assert!(registry.get("go").is_ok());
+        let err = registry.get("cobol").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown language 'cobol', known are go, rust"
+        );
+    }
+}
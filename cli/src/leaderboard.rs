@@ -0,0 +1,181 @@
+use std::{collections::BTreeMap, path::Path};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use colored::{ColoredString, Colorize};
+use eyre::{Context, ContextCompat, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::calendar::{format_solve_time, unlock_time, unlocked_days};
+
+/// The server only updates the JSON every 15 minutes and asks not to fetch it more often.
+pub const MIN_REFRESH_INTERVAL_MINUTES: i64 = 15;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub members: BTreeMap<String, Member>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub local_score: u64,
+    pub stars: u32,
+    #[serde(default)]
+    pub completion_day_level: BTreeMap<u32, BTreeMap<u32, Star>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Star {
+    pub get_star_ts: i64,
+}
+
+/// Leaderboard as it was saved after the last fetch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedLeaderboard {
+    pub fetched_at: DateTime<Utc>,
+    pub leaderboard: Leaderboard,
+}
+
+impl CachedLeaderboard {
+    pub async fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).await.ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+            .await
+            .with_context(|| format!("Couldn't write to '{}'", path.to_string_lossy()))
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        Utc::now() - self.fetched_at < Duration::minutes(MIN_REFRESH_INTERVAL_MINUTES)
+    }
+}
+
+impl Member {
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    pub fn stars_on(&self, day: u32) -> usize {
+        self.completion_day_level
+            .get(&day)
+            .map_or(0, |levels| levels.len())
+    }
+
+    pub fn star_time(&self, day: u32, level: u32) -> Option<DateTime<Utc>> {
+        let star = self.completion_day_level.get(&day)?.get(&level)?;
+        Utc.timestamp_opt(star.get_star_ts, 0).single()
+    }
+}
+
+impl Leaderboard {
+    fn ranked_members(&self) -> Vec<&Member> {
+        let mut members: Vec<_> = self.members.values().collect();
+        members.sort_by_key(|member| (std::cmp::Reverse(member.local_score), member.id));
+        members
+    }
+}
+
+pub fn print_overview(leaderboard: &Leaderboard, year: u32) {
+    let days = unlocked_days(year, Utc::now()).max(1);
+    let day_header: String = (1..=days).map(|day| (day % 10).to_string()).collect();
+    println!("{:>4} {:>6} {:>6}  {day_header}", "", "Score", "Stars");
+    for (rank, member) in leaderboard.ranked_members().into_iter().enumerate() {
+        let stars: String = (1..=days)
+            .map(|day| star_symbol(member.stars_on(day)).to_string())
+            .collect();
+        println!(
+            "{:>4} {:>6} {:>6}  {stars}  {}",
+            format!("{})", rank + 1),
+            member.local_score,
+            member.stars,
+            member.display_name()
+        );
+    }
+}
+
+pub fn print_day(leaderboard: &Leaderboard, year: u32, day: u32) -> Result<()> {
+    let unlock = unlock_time(year, day).with_context(|| format!("There is no day {day}"))?;
+    let solve_time = |member: &Member, level: u32| {
+        member
+            .star_time(day, level)
+            .map(|time| format_solve_time(time - unlock))
+    };
+
+    let mut members: Vec<_> = leaderboard.members.values().collect();
+    members.sort_by_key(|member| {
+        (
+            member.star_time(day, 2).is_none(),
+            member.star_time(day, 2),
+            member.star_time(day, 1).is_none(),
+            member.star_time(day, 1),
+        )
+    });
+
+    println!("{}", format!("Day {day}").cyan());
+    println!("{:>4} {:>12} {:>12}  Name", "", "Part 1", "Part 2");
+    for (rank, member) in members.into_iter().enumerate() {
+        let part_1 = solve_time(member, 1).unwrap_or_else(|| "-".into());
+        let part_2 = solve_time(member, 2).unwrap_or_else(|| "-".into());
+        println!(
+            "{:>4} {:>12} {:>12}  {}",
+            format!("{})", rank + 1),
+            part_1,
+            part_2,
+            member.display_name()
+        );
+    }
+    Ok(())
+}
+
+/// Prints the stars each member gained between the two fetches.
+pub fn print_gained_stars(previous: &CachedLeaderboard, current: &Leaderboard) {
+    let since = previous.fetched_at.format("%Y-%m-%d %H:%M:%S UTC");
+    let mut anyone_gained = false;
+    for member in current.ranked_members() {
+        let previous_member = previous.leaderboard.members.get(&member.id.to_string());
+        let gained: Vec<_> = member
+            .completion_day_level
+            .iter()
+            .flat_map(|(day, levels)| levels.keys().map(move |level| (*day, *level)))
+            .filter(|(day, level)| {
+                previous_member.is_none_or(|previous| previous.star_time(*day, *level).is_none())
+            })
+            .collect();
+        if gained.is_empty() {
+            continue;
+        }
+        anyone_gained = true;
+        let stars: Vec<_> = gained
+            .iter()
+            .map(|(day, level)| format!("day {day} part {level}"))
+            .collect();
+        println!(
+            "{} {} ({})",
+            member.display_name(),
+            format!("+{}", gained.len()).yellow(),
+            stars.join(", ")
+        );
+    }
+    if !anyone_gained {
+        println!("{}", format!("Nobody gained stars since {since}").cyan());
+    }
+}
+
+fn star_symbol(stars: usize) -> ColoredString {
+    match stars {
+        2 => "*".yellow(),
+        1 => "*".white(),
+        _ => ".".bright_black(),
+    }
+}
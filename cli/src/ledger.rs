@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::submit::SubmitOutcome;
+
+pub const LEDGER_FILE_NAME: &str = "submissions.toml";
+
+/// Every answer that was sent to the server for a single day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default, rename = "submission")]
+    pub submissions: Vec<Submission>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Submission {
+    pub level: u32,
+    pub answer: String,
+    pub outcome: SubmitOutcome,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Ledger {
+    pub fn path_next_to(config_path: &Path) -> PathBuf {
+        config_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(LEDGER_FILE_NAME)
+    }
+
+    pub async fn load(path: &Path) -> Result<Self> {
+        let mut ledger: Self = match fs::read_to_string(path).await {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("Failed to parse '{}'", path.to_string_lossy()))?,
+            Err(_) => Self::default(),
+        };
+        ledger.path = path.to_path_buf();
+        Ok(ledger)
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        fs::write(&self.path, toml::to_string_pretty(self)?)
+            .await
+            .with_context(|| format!("Couldn't write to '{}'", self.path.to_string_lossy()))
+    }
+
+    pub fn record(&mut self, level: u32, answer: &str, outcome: SubmitOutcome) {
+        self.submissions.push(Submission {
+            level,
+            answer: answer.to_string(),
+            outcome,
+            timestamp: Utc::now(),
+        });
+    }
+
+    pub fn accepted_answer(&self, level: u32) -> Option<&str> {
+        self.submissions_for_level(level)
+            .find(|submission| submission.outcome == SubmitOutcome::Correct)
+            .map(|submission| submission.answer.as_str())
+    }
+
+    /// Returns the reason why `answer` shouldn't be sent, if the ledger already
+    /// knows that it cannot be correct.
+    pub fn check(&self, level: u32, answer: &str) -> Option<String> {
+        if let Some(accepted) = self.accepted_answer(level) {
+            return Some(format!(
+                "Level {level} is already solved, the accepted answer was '{accepted}'."
+            ));
+        }
+
+        if let Some(previous) = self
+            .submissions_for_level(level)
+            .find(|submission| submission.answer == answer && submission.outcome.is_wrong())
+        {
+            return Some(format!(
+                "'{answer}' was already submitted at {} and was marked as {}.",
+                previous.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                previous.outcome
+            ));
+        }
+
+        let answer: i64 = answer.parse().ok()?;
+        let lowest_too_high = self.numeric_answers(level, SubmitOutcome::TooHigh).min();
+        if let Some(too_high) = lowest_too_high.filter(|too_high| answer >= *too_high) {
+            return Some(format!(
+                "{answer} cannot be correct, {too_high} was already too high."
+            ));
+        }
+        let highest_too_low = self.numeric_answers(level, SubmitOutcome::TooLow).max();
+        if let Some(too_low) = highest_too_low.filter(|too_low| answer <= *too_low) {
+            return Some(format!(
+                "{answer} cannot be correct, {too_low} was already too low."
+            ));
+        }
+
+        None
+    }
+
+    fn submissions_for_level(&self, level: u32) -> impl Iterator<Item = &Submission> {
+        self.submissions
+            .iter()
+            .filter(move |submission| submission.level == level)
+    }
+
+    fn numeric_answers(
+        &self,
+        level: u32,
+        outcome: SubmitOutcome,
+    ) -> impl Iterator<Item = i64> + '_ {
+        self.submissions_for_level(level)
+            .filter(move |submission| submission.outcome == outcome)
+            .filter_map(|submission| submission.answer.parse().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let mut ledger = Ledger::default();
+        ledger.record(1, "abc", SubmitOutcome::Wrong);
+        ledger.record(1, "100", SubmitOutcome::TooHigh);
+        ledger.record(1, "20", SubmitOutcome::TooLow);
+
+        assert!(ledger.check(1, "abc").is_some());
+        assert!(ledger.check(1, "100").is_some());
+        assert!(ledger.check(1, "150").is_some());
+        assert!(ledger.check(1, "10").is_some());
+        assert!(ledger.check(1, "50").is_none());
+        assert!(ledger.check(1, "def").is_none());
+        assert!(ledger.check(2, "100").is_none());
+
+        ledger.record(1, "50", SubmitOutcome::Correct);
+        assert!(ledger.check(1, "60").unwrap().contains("'50'"));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut ledger = Ledger::default();
+        ledger.record(1, "100", SubmitOutcome::TooHigh);
+        ledger.record(
+            1,
+            "50",
+            SubmitOutcome::RateLimited(std::time::Duration::from_secs(30)),
+        );
+        let serialized = toml::to_string_pretty(&ledger).unwrap();
+        let deserialized: Ledger = toml::from_str(&serialized).unwrap();
+        assert_eq!(
+            deserialized.submissions[1].outcome,
+            SubmitOutcome::RateLimited(std::time::Duration::from_secs(30))
+        );
+    }
+}
@@ -0,0 +1,24 @@
+//! Library behind the `aoc` CLI.
+//!
+//! The [`client::AocClient`] trait gives access to puzzles, inputs and
+//! submissions, either of the website through [`client::HttpClient`] or of an
+//! in-memory [`client::FakeClient`]. Solution crates can use it e.g. to fetch
+//! their input from a build script.
+
+pub mod archive;
+pub mod calendar;
+pub mod client;
+pub mod config;
+pub mod examples;
+pub mod http;
+pub mod languages;
+pub mod leaderboard;
+pub mod ledger;
+pub mod markdown;
+pub mod model;
+pub mod register;
+pub mod session;
+pub mod stats;
+pub mod status;
+pub mod submit;
+pub mod template;
@@ -1,66 +1,129 @@
-mod model;
-
-use crate::model::*;
+use aoc::{
+    archive::{checksum, day_dir_name, ArchivedDay, Manifest, INDEX_FILE_NAME},
+    calendar::{format_solve_time, next_unlock_day, parse_calendar_stars, unlock_time},
+    client::{AocClient, HttpClient, SubmitResponse},
+    config::{LoadedConfig, CONFIG_FILE_NAME},
+    examples::{new_examples, rank_examples, ExampleCandidate},
+    http::{AocHttp, CachePolicy, DEFAULT_THROTTLE},
+    languages::{Language, LanguageRegistry, LANGUAGES_FILE_NAME},
+    leaderboard::{self, CachedLeaderboard, Leaderboard, MIN_REFRESH_INTERVAL_MINUTES},
+    ledger::{Ledger, LEDGER_FILE_NAME},
+    markdown::{extract_articles, format_html_output},
+    model::*,
+    register::{check_workspace, find_day_links, find_repo_root, update_readme},
+    session::{account_cache_key, parse_account_name, SessionSource},
+    stats::{self, parse_personal_times, DayStats},
+    status::{self, find_local_days},
+    submit::SubmitOutcome,
+    template::{copy_template, parse_title, TemplateVars},
+};
 
 use std::{
-    collections::HashMap,
+    collections::BTreeMap,
     env,
     io::{self, Write},
-    path::Path,
+    path::{Path, PathBuf},
+    process::{self, Stdio},
+    time::Duration,
 };
 
-use async_recursion::async_recursion;
 use chrono::Datelike;
 use clap::Parser;
 use colored::Colorize;
-use eyre::{bail, Context, Result};
-use regex::{Captures, Regex};
-use reqwest::header::HeaderMap;
-use tokio::{fs, try_join};
+use eyre::{bail, Context, ContextCompat, Result};
+use regex::Regex;
+use tokio::{
+    fs,
+    io::{AsyncBufReadExt, BufReader},
+    try_join,
+};
 
+const PATH_CONFIG: &str = "./aoc.toml";
 const PATH_EXAMPLE: &str = "./example.txt";
 const PATH_INPUT: &str = "./input.txt";
 const PATH_PROBLEM: &str = "./problem.md";
+const PATH_EXAMPLE_PART_2: &str = "./example_part2";
+const PATH_SOLUTION_1: &str = "./src/bin/problem_1.rs";
+const PATH_SOLUTION_2: &str = "./src/bin/problem_2.rs";
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+const DEFAULT_LANGUAGE: &str = "rust";
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_AVAILABILITY_ATTEMPTS: u32 = 10;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    let config = load_config(&args).await;
-
-    let session = env::var("AOC_SESSION").context("AOC_SESSION not defined in environment")?;
+    let loaded = LoadedConfig::discover(args.config.as_deref()).await?;
+    if args.verbose {
+        for file in &loaded.files {
+            println!("{}", format!("Loaded '{}'", file.to_string_lossy()).cyan());
+        }
+    }
+    let config = &loaded.config;
+    let config_path = loaded
+        .day_file
+        .clone()
+        .unwrap_or_else(|| PATH_CONFIG.into());
+    let base_url = get_base_url(&args, config);
+
+    let session_source = SessionSource::resolve(
+        config,
+        args.profile.as_deref(),
+        &config_dir().join("sessions"),
+    );
 
-    let mut headers = HeaderMap::new();
-    headers.insert("cookie", format!("session={session}").parse()?);
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()?;
+    let user_agent = env::var("AOC_USER_AGENT").unwrap_or_else(|_| default_user_agent());
+    let throttle = match env::var("AOC_THROTTLE_MS") {
+        Ok(millis) => Duration::from_millis(millis.parse().context("Invalid AOC_THROTTLE_MS")?),
+        Err(_) => DEFAULT_THROTTLE,
+    };
+    let new_client = |session: Result<String>| {
+        // Inputs differ by account, so every session has its own cache
+        let account = match &session {
+            Ok(session) => account_cache_key(session),
+            Err(_) => "no-session".into(),
+        };
+        let http_cache_dir = cache_dir().join("http").join(account);
+        let http = AocHttp::new(session, &user_agent, http_cache_dir, throttle, args.refresh)?;
+        Ok::<_, eyre::Report>(HttpClient::new(http, &base_url))
+    };
+    let client = new_client(session_source.load().await)?;
 
     match args.command {
-        Command::New {
-            output,
+        Command::New { year, day, options } => {
+            let year = year.or(config.year).unwrap_or_else(get_default_year);
+            new_day(&client, config, year, day, options).await?;
+        }
+        Command::Wait { year, day, options } => {
+            let now = chrono::Utc::now();
+            let year = year.or(config.year).unwrap_or(now.year() as u32);
+            let day = match day {
+                Some(day) => day,
+                None => next_unlock_day(year, now)
+                    .with_context(|| format!("All puzzles of {year} are unlocked"))?,
+            };
+            wait_for_unlock(year, day).await?;
+            wait_until_available(&client, year, day).await?;
+            new_day(&client, config, year, day, options).await?;
+        }
+        Command::Download {
+            example,
             year,
             day,
-            template,
+            non_interactive,
+            example_index,
         } => {
-            let year = year.unwrap_or_else(get_default_year);
-            let output = output.unwrap_or_else(|| format!("./day_{day}").into());
-            create_project(year, day, &template, &output).await?;
-            env::set_current_dir(&output)?;
-            let (problem, _) = try_join!(
-                download_problem(&client, year, day, PATH_PROBLEM),
-                download_input(&client, year, day, PATH_INPUT),
-            )?;
-            println!("\n{}", "Problem:".cyan());
-            println!("{problem}\n\n");
-            let examples = download_potential_examples(&client, year, day).await?;
-            choose_and_save_correct_example(examples, PATH_EXAMPLE).await?;
-        }
-        Command::Download { example, year, day } => {
-            let year = get_year(year, &config)?;
-            let day = get_day(day, &config)?;
+            let year = get_year(year, config)?;
+            let day = get_day(day, config)?;
+            if let Some(day_file) = &loaded.day_file {
+                env::set_current_dir(get_day_dir(day_file))?;
+            }
+            refresh_puzzle(&client, year, day).await?;
             if example {
-                let examples = download_potential_examples(&client, year, day).await?;
-                choose_and_save_correct_example(examples, PATH_EXAMPLE).await?;
+                let examples = client.examples(year, day).await?;
+                let day_file = loaded.day_file.as_deref();
+                select_and_save_example(examples, non_interactive, example_index, day_file).await?;
             } else {
                 let (problem, _) = try_join!(
                     download_problem(&client, year, day, PATH_PROBLEM),
@@ -75,29 +138,479 @@ async fn main() -> Result<()> {
             year,
             day,
             level,
+            force,
         } => {
-            let year = get_year(year, &config)?;
-            let day = get_day(day, &config)?;
+            let year = get_year(year, config)?;
+            let day = get_day(day, config)?;
+            if let Some(day_file) = &loaded.day_file {
+                env::set_current_dir(get_day_dir(day_file))?;
+            }
+            let level = get_level(level).await?;
+            let mut ledger = Ledger::load(&Ledger::path_next_to(&config_path)).await?;
+            if let Some(reason) = ledger.check(level, &result).filter(|_| !force) {
+                bail!("{reason} Use --force to submit anyway.");
+            }
+            let outcome = submit_answer(&client, year, day, level, &result, &mut ledger).await?;
+            if outcome.exit_code() != 0 {
+                process::exit(outcome.exit_code());
+            }
+        }
+        Command::Run {
+            level,
+            example,
+            input: _,
+            submit,
+            force,
+        } => {
+            let Some(day_file) = &loaded.day_file else {
+                bail!("Couldn't find the '{CONFIG_FILE_NAME}' of a day");
+            };
+            env::set_current_dir(get_day_dir(day_file))?;
             let level = get_level(level).await?;
-            let response = submit_input(&client, year, day, level, &result).await?;
-            if !response.contains("not the right answer") {
-                download_problem(&client, year, day, PATH_PROBLEM).await?;
+            let file = if example { PATH_EXAMPLE } else { PATH_INPUT };
+            let answer = run_problem(level, file, &[]).await?;
+            println!("{}", format!("Answer: {answer}").green());
+            if submit {
+                let mut ledger = Ledger::load(Path::new(LEDGER_FILE_NAME)).await?;
+                if let Some(reason) = ledger.check(level, &answer).filter(|_| !force) {
+                    bail!("{reason} Use --force to submit anyway.");
+                }
+                let (year, day) = (get_year(None, config)?, get_day(None, config)?);
+                let outcome =
+                    submit_answer(&client, year, day, level, &answer, &mut ledger).await?;
+                if outcome.exit_code() != 0 {
+                    process::exit(outcome.exit_code());
+                }
+            }
+        }
+        Command::Check { level } => {
+            let Some(day_file) = &loaded.day_file else {
+                bail!("Couldn't find the '{CONFIG_FILE_NAME}' of a day");
+            };
+            env::set_current_dir(get_day_dir(day_file))?;
+            let examples: Vec<_> = config
+                .examples
+                .iter()
+                .filter(|example| level.is_none_or(|level| example.level == level))
+                .collect();
+            if examples.is_empty() {
+                bail!("No examples defined in '{}'", day_file.to_string_lossy());
+            }
+
+            let mut failed = 0;
+            for example in &examples {
+                if !check_example(example).await? {
+                    failed += 1;
+                }
+            }
+            if failed > 0 {
+                bail!("{failed} of {} examples failed", examples.len());
             }
-            println!("\nResponse:\n{response}");
+            println!(
+                "{}",
+                format!("All {} examples passed", examples.len()).green()
+            );
+        }
+        Command::Leaderboard {
+            id,
+            year,
+            day,
+            since,
+        } => {
+            let year = get_year(year, config).unwrap_or_else(|_| get_default_year());
+            let cache_path = cache_dir().join(format!("leaderboard_{year}_{id}.json"));
+            let previous = CachedLeaderboard::load(&cache_path).await;
+            let leaderboard = match &previous {
+                Some(previous) if previous.is_fresh() => {
+                    let fetched_at = previous.fetched_at.format("%H:%M:%S UTC");
+                    println!(
+                        "{}",
+                        format!("Using the leaderboard fetched at {fetched_at}").cyan()
+                    );
+                    previous.leaderboard.clone()
+                }
+                _ => {
+                    let leaderboard = download_leaderboard(&client, year, id).await?;
+                    let cached = CachedLeaderboard {
+                        fetched_at: chrono::Utc::now(),
+                        leaderboard: leaderboard.clone(),
+                    };
+                    cached.save(&cache_path).await?;
+                    leaderboard
+                }
+            };
+            match (since, day, previous) {
+                // Nothing new was fetched, the diff would always be empty
+                (true, _, Some(previous)) if previous.is_fresh() => println!(
+                    "{}",
+                    format!(
+                        "No new data was fetched, the leaderboard is only refreshed every \
+                         {MIN_REFRESH_INTERVAL_MINUTES} minutes"
+                    )
+                    .cyan()
+                ),
+                (true, _, Some(previous)) => {
+                    leaderboard::print_gained_stars(&previous, &leaderboard)
+                }
+                (true, _, None) => println!("{}", "No previous fetch found".cyan()),
+                (false, Some(day), _) => leaderboard::print_day(&leaderboard, year, day)?,
+                (false, None, _) => leaderboard::print_overview(&leaderboard, year),
+            }
+        }
+        Command::Login { check } => {
+            let client = match check {
+                true => client,
+                false => {
+                    print!("{}", "> Session cookie: ".cyan());
+                    io::stdout().flush()?;
+                    let session = read_user_input()?;
+                    let path = session_source.save(&session).await?;
+                    println!("Saved session to '{}'", path.to_string_lossy());
+                    new_client(Ok(session))?
+                }
+            };
+            let url = format!("{}/settings", client.base_url());
+            let html = client.http().get_text(&url, CachePolicy::Bypass).await?;
+            match parse_account_name(&html)? {
+                Some(name) => println!("{}", format!("Logged in as {name}").green()),
+                None => bail!("The session cookie is invalid or expired"),
+            }
+        }
+        Command::Status { year, path } => {
+            let year = get_year(year, config).unwrap_or_else(|_| get_default_year());
+            let html = download_calendar_html(&client, year).await?;
+            let stars = parse_calendar_stars(&html)?;
+            let local_days = find_local_days(&path, year).await?;
+            status::print_status(year, &stars, &local_days);
+        }
+        Command::Stats { year, path, format } => {
+            let year = get_year(year, config).unwrap_or_else(|_| get_default_year());
+            let url = format!("{}/{year}/leaderboard/self", client.base_url());
+            let html = client.http().get_text(&url, CachePolicy::Bypass).await?;
+            let mut days = parse_personal_times(&html)?;
+            for (day, local_day) in find_local_days(&path, year).await? {
+                let ledger = Ledger::load(&local_day.dir.join(LEDGER_FILE_NAME)).await?;
+                days.entry(day)
+                    .or_insert_with(|| DayStats {
+                        day,
+                        ..Default::default()
+                    })
+                    .add_ledger(year, &ledger);
+            }
+            match format {
+                StatsFormat::Table => stats::print_stats(&days),
+                StatsFormat::Csv => print!("{}", stats::to_csv(&days)),
+                StatsFormat::Json => {
+                    let days: Vec<_> = days.values().collect();
+                    println!("{}", serde_json::to_string_pretty(&days)?);
+                }
+            }
+        }
+        Command::Archive { year, output } => {
+            let year = get_year(year, config).unwrap_or_else(|_| get_default_year());
+            archive_year(&client, year, &output.join(year.to_string())).await?;
         }
     }
 
     Ok(())
 }
 
-fn get_year(arg: Option<u32>, config: &Option<Config>) -> Result<u32> {
-    if let Some(arg) = arg {
-        return Ok(arg);
+/// Archives every unlocked day of the year to `dir`, skipping the days that are
+/// archived with all their stars and intact files.
+async fn archive_year(client: &HttpClient, year: u32, dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("Couldn't create '{}'", dir.to_string_lossy()))?;
+    let mut manifest = Manifest::load(dir, year).await?;
+    let html = download_calendar_html(client, year).await?;
+    for (day, stars) in parse_calendar_stars(&html)? {
+        let day_dir = dir.join(day_dir_name(day));
+        if let Some(archived) = manifest.day(day).filter(|archived| archived.stars >= stars) {
+            let damaged = archived.damaged_files(&day_dir).await;
+            if damaged.is_empty() {
+                println!("Day {day} is already archived");
+                continue;
+            }
+            println!(
+                "{}",
+                format!(
+                    "Day {day}: {} don't match the manifest, archiving again",
+                    damaged.join(", ")
+                )
+                .yellow()
+            );
+        }
+        let archived = archive_day(client, year, day, stars, &day_dir).await?;
+        manifest.insert(archived);
+        // Saved after every day to keep the progress of interrupted runs
+        manifest.save(dir).await?;
     }
-    if let Some(config) = config {
-        return Ok(config.year);
+    manifest.save(dir).await?;
+    save(
+        &dir.join(INDEX_FILE_NAME).to_string_lossy(),
+        &manifest.render_index(),
+    )
+    .await
+}
+
+async fn archive_day(
+    client: &HttpClient,
+    year: u32,
+    day: u32,
+    stars: u32,
+    dir: &Path,
+) -> Result<ArchivedDay> {
+    fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("Couldn't create '{}'", dir.to_string_lossy()))?;
+    let path = |file: &str| dir.join(file).to_string_lossy().to_string();
+    // The cached page might predate stars gained outside of the CLI
+    client
+        .http()
+        .invalidate(&client.puzzle_url(year, day))
+        .await?;
+    let html = client.puzzle(year, day).await?;
+    save(&path("puzzle.html"), &extract_articles(&html)?).await?;
+    let (problem_path, input_path) = (path("problem.md"), path("input.txt"));
+    try_join!(
+        download_problem(client, year, day, &problem_path),
+        download_input(client, year, day, &input_path),
+    )?;
+    for (i, example) in client.examples(year, day).await?.iter().enumerate() {
+        save(&path(&format!("example_{i}.txt")), &example.text).await?;
     }
-    bail!("Missing argument 'year'");
+
+    let mut files = BTreeMap::new();
+    let mut read_dir = fs::read_dir(dir).await?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        let content = fs::read(entry.path()).await?;
+        files.insert(
+            entry.file_name().to_string_lossy().to_string(),
+            checksum(&content),
+        );
+    }
+    Ok(ArchivedDay {
+        day,
+        title: parse_title(&html),
+        stars,
+        files,
+    })
+}
+
+/// Creates the directory of a day from the template of its language and
+/// downloads the puzzle, input and example.
+async fn new_day(
+    client: &HttpClient,
+    config: &Config,
+    year: u32,
+    day: u32,
+    options: NewOptions,
+) -> Result<()> {
+    let NewOptions {
+        output,
+        lang,
+        template,
+        non_interactive,
+        example_index,
+    } = options;
+    let languages = LanguageRegistry::load(&config_dir().join(LANGUAGES_FILE_NAME)).await?;
+    let config_lang = config.lang.as_deref().unwrap_or(DEFAULT_LANGUAGE);
+    let lang = lang.unwrap_or_else(|| config_lang.to_string());
+    let language = languages.get(&lang)?;
+    // The template of the config belongs to its language
+    let template = template.or_else(|| match lang == config_lang {
+        true => config.template.clone(),
+        false => None,
+    });
+    let output = output.unwrap_or_else(|| format!("./day_{day}").into());
+    refresh_puzzle(client, year, day).await?;
+    let html = client.puzzle(year, day).await?;
+    let vars = TemplateVars::new(year, day, parse_title(&html).as_deref());
+    create_project(year, day, language, template.as_deref(), &output, &vars).await?;
+    register_day(year, day, &output).await?;
+    env::set_current_dir(&output)?;
+    let (problem, _) = try_join!(
+        download_problem(client, year, day, PATH_PROBLEM),
+        download_input(client, year, day, PATH_INPUT),
+    )?;
+    println!("\n{}", "Problem:".cyan());
+    println!("{problem}\n\n");
+    let examples = client.examples(year, day).await?;
+    select_and_save_example(
+        examples,
+        non_interactive,
+        example_index,
+        Some(Path::new(PATH_CONFIG)),
+    )
+    .await
+}
+
+/// Shows a countdown until the puzzle unlocks.
+async fn wait_for_unlock(year: u32, day: u32) -> Result<()> {
+    let unlock = unlock_time(year, day).with_context(|| format!("There is no day {day}"))?;
+    loop {
+        let remaining = unlock - chrono::Utc::now();
+        let Ok(remaining_std) = remaining.to_std() else {
+            break;
+        };
+        print!(
+            "\r{}",
+            format!(
+                "Day {day} unlocks in {}",
+                format_solve_time(remaining + chrono::Duration::seconds(1))
+            )
+            .cyan()
+        );
+        io::stdout().flush()?;
+        tokio::time::sleep(remaining_std.min(Duration::from_secs(1))).await;
+    }
+    println!("\r{}", format!("Day {day} is unlocked").green());
+    Ok(())
+}
+
+/// Polls the puzzle page with exponential backoff until it stops returning 404,
+/// as the server might need a moment after the unlock.
+async fn wait_until_available(client: &HttpClient, year: u32, day: u32) -> Result<()> {
+    let mut backoff = INITIAL_BACKOFF;
+    for _ in 0..MAX_AVAILABILITY_ATTEMPTS {
+        let url = client.puzzle_url(year, day);
+        match client.http().get_text(&url, CachePolicy::Refresh).await {
+            Ok(_) => return Ok(()),
+            Err(err) if is_not_found(&err) => {
+                println!(
+                    "{}",
+                    format!("Puzzle not available yet, retry in {}s", backoff.as_secs()).yellow()
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    bail!("Puzzle is still not available after {MAX_AVAILABILITY_ATTEMPTS} attempts");
+}
+
+fn is_not_found(err: &eyre::Report) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(|err| err.status())
+        .is_some_and(|status| status == reqwest::StatusCode::NOT_FOUND)
+}
+
+async fn submit_answer(
+    client: &HttpClient,
+    year: u32,
+    day: u32,
+    level: u32,
+    answer: &str,
+    ledger: &mut Ledger,
+) -> Result<SubmitOutcome> {
+    // Taken before posting, as the page shows Part Two once Part One is solved
+    let part_one_examples = match level {
+        1 => Some(client.examples(year, day).await?),
+        _ => None,
+    };
+    let SubmitResponse {
+        message: response,
+        mut outcome,
+    } = client.submit(year, day, level, answer).await?;
+    let mut accepted_answer = None;
+    if outcome == SubmitOutcome::WrongLevel {
+        let accepted_answers = download_accepted_answers(client, year, day).await?;
+        if let Some(answer) = accepted_answers.into_iter().nth(level as usize - 1) {
+            outcome = SubmitOutcome::AlreadySolved;
+            accepted_answer = Some(answer);
+        }
+    }
+    ledger.record(level, answer, outcome.clone());
+    ledger.save().await?;
+    if outcome == SubmitOutcome::Correct {
+        // The page gained the next part or the answer
+        client
+            .http()
+            .invalidate(&client.puzzle_url(year, day))
+            .await?;
+        download_problem(client, year, day, PATH_PROBLEM).await?;
+        if let Some(part_one_examples) = part_one_examples {
+            prepare_part_two(client, year, day, &part_one_examples).await?;
+        }
+    }
+    println!("\nResponse:\n{response}");
+    println!("{}", outcome.summary());
+    if let Some(answer) = accepted_answer {
+        println!(
+            "{}",
+            format!("The accepted answer was '{answer}'.").yellow()
+        );
+    }
+    Ok(outcome)
+}
+
+/// Saves the examples Part Two added to the page and starts its solution from
+/// the one of Part One.
+async fn prepare_part_two(
+    client: &HttpClient,
+    year: u32,
+    day: u32,
+    part_one_examples: &[ExampleCandidate],
+) -> Result<()> {
+    let examples = new_examples(part_one_examples, &client.examples(year, day).await?);
+    for (i, example) in examples.iter().enumerate() {
+        let file = match i {
+            0 => format!("{PATH_EXAMPLE_PART_2}.txt"),
+            _ => format!("{PATH_EXAMPLE_PART_2}_{}.txt", i + 1),
+        };
+        save(&file, &example.text).await?;
+    }
+    if Path::new(PATH_SOLUTION_1).is_file() && !Path::new(PATH_SOLUTION_2).exists() {
+        println!(
+            "{}",
+            format!("Copying {PATH_SOLUTION_1} to {PATH_SOLUTION_2}").cyan()
+        );
+        fs::copy(PATH_SOLUTION_1, PATH_SOLUTION_2)
+            .await
+            .with_context(|| format!("Couldn't write to {PATH_SOLUTION_2}"))?;
+    }
+    Ok(())
+}
+
+fn get_base_url(args: &Args, config: &Config) -> String {
+    let base_url = args
+        .base_url
+        .clone()
+        .or_else(|| config.base_url.clone())
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+    base_url.trim_end_matches('/').to_string()
+}
+
+fn default_user_agent() -> String {
+    let name = env!("CARGO_PKG_NAME");
+    let version = env!("CARGO_PKG_VERSION");
+    match env::var("AOC_CONTACT") {
+        Ok(contact) => format!("{name}/{version} (+{contact})"),
+        Err(_) => format!("{name}/{version}"),
+    }
+}
+
+fn get_year(arg: Option<u32>, config: &Config) -> Result<u32> {
+    arg.or(config.year).context("Missing argument 'year'")
+}
+
+/// Directory for user settings, like `$XDG_CONFIG_HOME/aoc`.
+fn config_dir() -> PathBuf {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .unwrap_or_else(env::temp_dir);
+    base.join("aoc")
+}
+
+/// Directory for data that can be fetched again, like `$XDG_CACHE_HOME/aoc`.
+fn cache_dir() -> PathBuf {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .unwrap_or_else(env::temp_dir);
+    base.join("aoc")
 }
 
 fn get_default_year() -> u32 {
@@ -110,14 +623,8 @@ fn get_default_year() -> u32 {
     }
 }
 
-fn get_day(arg: Option<u32>, config: &Option<Config>) -> Result<u32> {
-    if let Some(arg) = arg {
-        return Ok(arg);
-    }
-    if let Some(config) = config {
-        return Ok(config.day);
-    }
-    bail!("Missing argument 'day'");
+fn get_day(arg: Option<u32>, config: &Config) -> Result<u32> {
+    arg.or(config.day).context("Missing argument 'day'")
 }
 
 async fn get_level(arg: Option<u32>) -> Result<u32> {
@@ -130,53 +637,77 @@ async fn get_level(arg: Option<u32>) -> Result<u32> {
         .await
         .context(format!("Error while opening '{PATH_PROBLEM}'"))?;
 
-    match problem_text.contains("--- Part Two ---") {
+    let has_part_two =
+        problem_text.contains("## Part Two") || problem_text.contains("--- Part Two ---");
+    match has_part_two {
         true => Ok(2),
         false => Ok(1),
     }
 }
 
-async fn load_config(args: &Args) -> Option<Config> {
-    let path = &args.config;
-    let file_content = fs::read_to_string(&path).await.ok()?;
-    let config: Config = match toml::from_str(&file_content) {
-        Ok(config) => config,
-        Err(err) => {
-            eprintln!(
-                "Failed to parse config {path}: {err}",
-                path = &path.to_string_lossy()
-            );
-            return None;
-        }
-    };
-    Some(config)
+fn get_day_dir(config_path: &Path) -> &Path {
+    match config_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
 }
 
-async fn create_project(year: u32, day: u32, template: &Path, output: &Path) -> Result<()> {
-    let output_str = output.to_string_lossy();
-    let template_str = template.to_string_lossy();
-    println!("Copy '{template_str}' to '{output_str}'");
-    copy_dir_all(template, output)
+/// Updates the config of a day, files without a day are left untouched.
+async fn update_config(path: &Path, update: impl FnOnce(&mut Config)) -> Result<()> {
+    let path_str = path.to_string_lossy();
+    let content = fs::read_to_string(path)
         .await
-        .context(format!("Failed to copy '{template_str}' to '{output_str}'"))?;
-
-    // Replace project name for rust projects
-    let cargo_toml = &output.join("Cargo.toml");
-    if let Ok(content) = fs::read_to_string(cargo_toml).await {
-        let cargo_toml_str = cargo_toml.to_string_lossy();
-        println!("Change name in '{cargo_toml_str}'");
-        fs::write(
-            cargo_toml,
-            content.replace(r#"name = "template""#, &format!(r#"name = "day_{day}""#)),
-        )
+        .with_context(|| format!("Couldn't read '{path_str}'"))?;
+    let mut config: Config =
+        toml::from_str(&content).with_context(|| format!("Failed to parse '{path_str}'"))?;
+    if config.day.is_none() {
+        let note = format!("'{path_str}' doesn't belong to a day, leaving it unchanged");
+        println!("{}", note.yellow());
+        return Ok(());
+    }
+    update(&mut config);
+    fs::write(path, toml::to_string_pretty(&config)?)
         .await
-        .context(format!("Failed to update '{cargo_toml_str}'"))?;
+        .with_context(|| format!("Couldn't write to '{path_str}'"))?;
+    Ok(())
+}
+
+async fn create_project(
+    year: u32,
+    day: u32,
+    language: &Language,
+    template: Option<&Path>,
+    output: &Path,
+    vars: &TemplateVars,
+) -> Result<()> {
+    let output_str = output.to_string_lossy();
+    match template.or(language.template.as_deref()) {
+        Some(template) => {
+            let template_str = template.to_string_lossy();
+            println!("Copy '{template_str}' to '{output_str}'");
+            copy_template(template, output, vars)
+                .await
+                .context(format!("Failed to copy '{template_str}' to '{output_str}'"))?;
+        }
+        None => {
+            println!("Create '{output_str}'");
+            fs::create_dir_all(output)
+                .await
+                .context(format!("Failed to create '{output_str}'"))?;
+        }
+    }
+    for step in &language.steps {
+        step.run(output, vars).await?;
     }
 
     // Add aoc config
     let aoc_toml = &output.join("aoc.toml");
     let aoc_toml_str = aoc_toml.to_string_lossy();
-    let config = Config { year, day };
+    let config = Config {
+        year: Some(year),
+        day: Some(day),
+        ..Default::default()
+    };
     println!("Generate '{aoc_toml_str}'");
     fs::write(aoc_toml, toml::to_string_pretty(&config)?)
         .await
@@ -185,101 +716,143 @@ async fn create_project(year: u32, day: u32, template: &Path, output: &Path) ->
     Ok(())
 }
 
+/// Adds the day to the README of the repository and checks that the Cargo
+/// workspace of its language picks it up.
+async fn register_day(year: u32, day: u32, output: &Path) -> Result<()> {
+    let output = fs::canonicalize(output).await?;
+    let Some(root) = find_repo_root(&output) else {
+        return Ok(());
+    };
+    let readme_path = root.join("README.md");
+    let readme_str = readme_path.to_string_lossy();
+    let links = find_day_links(&root, day).await?;
+    let readme = fs::read_to_string(&readme_path)
+        .await
+        .with_context(|| format!("Couldn't read '{readme_str}'"))?;
+    let updated = update_readme(&readme, year, day, &links);
+    if updated != readme {
+        println!("Add day {day} to '{readme_str}'");
+        fs::write(&readme_path, updated)
+            .await
+            .with_context(|| format!("Couldn't write to '{readme_str}'"))?;
+    }
+
+    if let (Some(language_dir), Some(name)) = (output.parent(), output.file_name()) {
+        if let Some(problem) = check_workspace(language_dir, &name.to_string_lossy()).await? {
+            println!("{}", format!("Warning: {problem}").yellow());
+        }
+    }
+    Ok(())
+}
+
 async fn download_problem(
-    client: &reqwest::Client,
+    client: &HttpClient,
     year: u32,
     day: u32,
     output_file: &str,
 ) -> Result<String> {
-    let url = format!("https://adventofcode.com/{year}/day/{day}");
-    let html = client
-        .get(url)
-        .send()
-        .await?
-        .error_for_status()?
-        .text()
-        .await?;
+    let html = client.puzzle(year, day).await?;
     let article = format_html_output(&html)?;
     save(output_file, &article).await?;
     Ok(article)
 }
 
-async fn download_input(
-    client: &reqwest::Client,
-    year: u32,
-    day: u32,
-    output_file: &str,
-) -> Result<()> {
-    let url = format!("https://adventofcode.com/{year}/day/{day}/input");
-    let input_text = client
-        .get(url)
-        .send()
-        .await?
-        .error_for_status()?
-        .text()
-        .await?;
-    save(output_file, &input_text).await?;
+/// Fetches the puzzle page again, as levels might have been solved on the
+/// website, the following reads of the command use the cached copy.
+async fn refresh_puzzle(client: &HttpClient, year: u32, day: u32) -> Result<()> {
+    let url = client.puzzle_url(year, day);
+    client.http().get_text(&url, CachePolicy::Refresh).await?;
     Ok(())
 }
 
-async fn submit_input(
-    client: &reqwest::Client,
-    year: u32,
-    day: u32,
-    level: u32,
-    result: &str,
-) -> Result<String> {
-    let url = format!("https://adventofcode.com/{year}/day/{day}/answer");
-    let mut form = HashMap::new();
-    form.insert("level", format!("{level}"));
-    form.insert("answer", result.into());
-    let response = client
-        .post(url)
-        .form(&form)
-        .send()
-        .await?
-        .error_for_status()?
-        .text()
-        .await?;
-    let response = format_html_output(&response)?;
-    Ok(response)
-}
-
-async fn download_potential_examples(
-    client: &reqwest::Client,
+async fn download_accepted_answers(
+    client: &HttpClient,
     year: u32,
     day: u32,
 ) -> Result<Vec<String>> {
-    let url = format!("https://adventofcode.com/{year}/day/{day}");
-    let html = client
-        .get(url)
-        .send()
-        .await?
-        .error_for_status()?
-        .text()
-        .await?;
-    let dom = tl::parse(&html, Default::default())?;
-    let parser = dom.parser();
-    let examples: Vec<String> = dom
-        .query_selector("pre")
-        .unwrap()
-        .map(|element| element.get(parser).unwrap())
-        .map(|node| {
-            node.inner_text(parser)
-                .to_string()
-                .replace("&lt;", "<")
-                .replace("&gt;", ">")
-        })
+    // The cached page might predate answers given on the website
+    let url = client.puzzle_url(year, day);
+    let html = client.http().get_text(&url, CachePolicy::Refresh).await?;
+    let answer_regex = Regex::new(r"Your puzzle answer was <code>([^<]*)</code>").unwrap();
+    let answers = answer_regex
+        .captures_iter(&html)
+        .map(|captures| captures[1].to_string())
         .collect();
-    Ok(examples)
+    Ok(answers)
+}
+
+async fn download_calendar_html(client: &HttpClient, year: u32) -> Result<String> {
+    let url = format!("{}/{year}", client.base_url());
+    client.http().get_text(&url, CachePolicy::Bypass).await
+}
+
+async fn download_leaderboard(client: &HttpClient, year: u32, id: u64) -> Result<Leaderboard> {
+    let url = format!(
+        "{}/{year}/leaderboard/private/view/{id}.json",
+        client.base_url()
+    );
+    client.http().get_json(&url).await
 }
 
-async fn choose_and_save_correct_example(examples: Vec<String>, output_file: &str) -> Result<()> {
+async fn download_input(client: &HttpClient, year: u32, day: u32, output_file: &str) -> Result<()> {
+    let input_text = client.input(year, day).await?;
+    save(output_file, &input_text).await?;
+    Ok(())
+}
+
+/// Saves the selected example to `PATH_EXAMPLE` and records its index in the
+/// config of the day, if there is one.
+///
+/// In non-interactive mode all candidates are saved as `example_<i>.txt` and,
+/// unless `index` is given, the most likely example is selected.
+async fn select_and_save_example(
+    examples: Vec<ExampleCandidate>,
+    non_interactive: bool,
+    index: Option<usize>,
+    config_path: Option<&Path>,
+) -> Result<()> {
     if examples.is_empty() {
         println!("{}", "No examples found.".cyan());
         return Ok(());
     }
 
+    if non_interactive {
+        for (i, example) in examples.iter().enumerate() {
+            save(&format!("./example_{i}.txt"), &example.text).await?;
+        }
+    }
+
+    let index = match index {
+        Some(index) => Some(index),
+        None if non_interactive => rank_examples(&examples),
+        None => choose_example(&examples)?,
+    };
+    let Some(index) = index else {
+        println!();
+        println!("{}", "Nothing selected".cyan());
+        return Ok(());
+    };
+    let example = examples.get(index).with_context(|| {
+        format!(
+            "Example {index} doesn't exist, only {} were found",
+            examples.len()
+        )
+    })?;
+    println!("{}", format!("Selected example {index}").cyan());
+    save(PATH_EXAMPLE, &example.text).await?;
+    match config_path {
+        Some(config_path) => {
+            update_config(config_path, |config| config.example_index = Some(index)).await
+        }
+        None => {
+            let note = format!("No '{CONFIG_FILE_NAME}' of a day found, not recording the index");
+            println!("{}", note.yellow());
+            Ok(())
+        }
+    }
+}
+
+fn choose_example(examples: &[ExampleCandidate]) -> Result<Option<usize>> {
     println!(
         "{}",
         format!(
@@ -291,7 +864,7 @@ async fn choose_and_save_correct_example(examples: Vec<String>, output_file: &st
     for (i, example) in examples.iter().enumerate().rev() {
         println!();
         println!("{}", format!("Example {}:", i).cyan());
-        let lines: Vec<_> = example.lines().collect();
+        let lines: Vec<_> = example.text.lines().collect();
         let short_example = limit_size(&lines, 10).join("\n");
         println!("{}", short_example);
     }
@@ -299,51 +872,59 @@ async fn choose_and_save_correct_example(examples: Vec<String>, output_file: &st
     print!("{}", "> Choose example: ".cyan());
     io::stdout().flush()?;
     match read_user_input()?.parse::<usize>() {
-        Ok(n) if n < examples.len() => {
-            save(output_file, &examples[n]).await?;
-            Ok(())
-        }
-        _ => {
-            println!();
-            println!("{}", "Nothing selected".cyan());
-            Ok(())
-        }
+        Ok(n) if n < examples.len() => Ok(Some(n)),
+        _ => Ok(None),
     }
 }
 
-fn limit_size<T>(list: &[T], limit: usize) -> &[T] {
-    list.get(..limit).unwrap_or(list)
-}
-
-fn format_html_output(html: &str) -> Result<String> {
-    let dom = tl::parse(html, Default::default())?;
-    let parser = dom.parser();
-    let articles: Vec<_> = dom
-        .query_selector("article")
-        .unwrap()
-        .map(|node| node.get(parser).unwrap().inner_html(parser))
-        .collect();
-    let articles = articles.join("\n");
-    let html = format!("<div>{articles}</div>");
-    let article = html_to_text(&html);
-    Ok(article)
+async fn check_example(example: &Example) -> Result<bool> {
+    let Example {
+        level,
+        file,
+        expected,
+        args,
+    } = example;
+    let actual = run_problem(*level, file, args).await?;
+    let name = format!("Level {level} with '{file}'");
+    if &actual == expected {
+        println!("{}", format!("PASS {name}").green());
+        return Ok(true);
+    }
+    println!("{}", format!("FAIL {name}").red());
+    println!("{}", format!("- expected: {expected}").red());
+    println!("{}", format!("+ actual:   {actual}").green());
+    Ok(false)
 }
 
-fn html_to_text(html: &str) -> String {
-    let text = html2text::from_read(html.as_bytes(), 80);
-
-    // Wrap multiline code examples with triple ```
-    let code_regex = Regex::new(r"`([^`]+)`").unwrap();
-    let text = code_regex.replace_all(&text, |caps: &Captures| {
-        let content = &caps[1];
-        if content.contains('\n') {
-            format!("```\n{content}\n```")
-        } else {
-            format!("`{content}`")
+/// Runs the problem binary in release mode and returns the last line it printed.
+async fn run_problem(level: u32, input_file: &str, args: &[String]) -> Result<String> {
+    let bin = format!("problem_{level}");
+    println!("{}", format!("Run '{bin}' with '{input_file}'").cyan());
+    let mut child = tokio::process::Command::new("cargo")
+        .args(["run", "--release", "-q", "--bin", &bin, input_file])
+        .args(args)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to start cargo")?;
+
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut answer = None;
+    while let Some(line) = lines.next_line().await? {
+        println!("{line}");
+        if !line.trim().is_empty() {
+            answer = Some(line.trim().to_string());
         }
-    });
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        bail!("'{bin}' failed with {status}");
+    }
+    answer.with_context(|| format!("'{bin}' didn't print an answer"))
+}
 
-    text.to_string()
+fn limit_size<T>(list: &[T], limit: usize) -> &[T] {
+    list.get(..limit).unwrap_or(list)
 }
 
 fn read_user_input() -> io::Result<String> {
@@ -359,22 +940,3 @@ async fn save(file: &str, content: &str) -> Result<()> {
         .with_context(|| format!("Couldn't write to {file}"))?;
     Ok(())
 }
-
-#[async_recursion]
-async fn copy_dir_all<S, D>(src: S, dst: D) -> io::Result<()>
-where
-    S: AsRef<Path> + Send + Sync,
-    D: AsRef<Path> + Send + Sync,
-{
-    fs::create_dir_all(&dst).await?;
-    let mut read_dir = fs::read_dir(src).await?;
-    while let Ok(Some(entry)) = read_dir.next_entry().await {
-        let file_type = entry.file_type().await?;
-        if file_type.is_dir() {
-            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name())).await?;
-        } else {
-            fs::copy(entry.path(), dst.as_ref().join(entry.file_name())).await?;
-        }
-    }
-    Ok(())
-}
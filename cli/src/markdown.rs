@@ -0,0 +1,259 @@
+use eyre::Result;
+use tl::{HTMLTag, Node, NodeHandle, Parser};
+
+use crate::examples::decode_html_entities;
+
+/// Converts the HTML of a puzzle article to Markdown.
+///
+/// Emphasis is rendered bold, as it is used to highlight the important numbers,
+/// `<pre>` blocks are kept verbatim in code fences and the `--- Part Two ---`
+/// titles become headings.
+pub fn html_to_markdown(html: &str) -> Result<String> {
+    let dom = tl::parse(html, Default::default())?;
+    let converter = Converter {
+        parser: dom.parser(),
+    };
+    let mut blocks = Vec::new();
+    converter.blocks(dom.children(), &mut blocks);
+    Ok(blocks.join("\n\n") + "\n")
+}
+
+/// Converts the `<article>` elements of a page to Markdown, which is where the
+/// puzzle and the responses to submissions are.
+pub fn format_html_output(html: &str) -> Result<String> {
+    html_to_markdown(&extract_articles(html)?)
+}
+
+/// The HTML of the `<article>` elements of a page, one per line.
+pub fn extract_articles(html: &str) -> Result<String> {
+    let dom = tl::parse(html, Default::default())?;
+    let parser = dom.parser();
+    let articles: Vec<_> = dom
+        .query_selector("article")
+        .unwrap()
+        .map(|node| node.get(parser).unwrap().outer_html(parser))
+        .collect();
+    Ok(articles.join("\n"))
+}
+
+struct Converter<'p, 'a> {
+    parser: &'p Parser<'a>,
+}
+
+impl Converter<'_, '_> {
+    fn blocks(&self, handles: &[NodeHandle], blocks: &mut Vec<String>) {
+        for handle in handles {
+            let Some(node) = handle.get(self.parser) else {
+                continue;
+            };
+            let Some(tag) = node.as_tag() else {
+                let text = self.inline(std::slice::from_ref(handle));
+                if !text.trim().is_empty() {
+                    blocks.push(text.trim().to_string());
+                }
+                continue;
+            };
+            match tag_name(tag).as_str() {
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => blocks.push(self.heading(tag)),
+                "pre" => blocks.push(self.code_block(tag)),
+                "ul" | "ol" => blocks.push(self.list(tag, 0)),
+                "article" | "main" | "section" | "div" | "body" | "html" => {
+                    self.blocks(tag.children().top().as_slice(), blocks)
+                }
+                "head" | "script" | "style" | "form" => {}
+                _ => {
+                    let text = self.inline(std::slice::from_ref(handle));
+                    if !text.trim().is_empty() {
+                        blocks.push(text.trim().to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    fn heading(&self, tag: &HTMLTag) -> String {
+        let level: usize = tag_name(tag)[1..].parse().unwrap_or(1);
+        let title = self.inline(tag.children().top().as_slice());
+        let title = title.trim().trim_matches('-').trim();
+        format!("{} {title}", "#".repeat(level))
+    }
+
+    fn code_block(&self, tag: &HTMLTag) -> String {
+        let code = decode_html_entities(&tag.inner_text(self.parser));
+        let code = code.strip_suffix('\n').unwrap_or(&code);
+        format!("```\n{code}\n```")
+    }
+
+    fn list(&self, tag: &HTMLTag, depth: usize) -> String {
+        let ordered = tag_name(tag) == "ol";
+        let indent = "  ".repeat(depth);
+        let mut lines = Vec::new();
+        let children = tag.children();
+        let items = children
+            .top()
+            .iter()
+            .filter_map(|handle| handle.get(self.parser)?.as_tag())
+            .filter(|child| tag_name(child) == "li");
+        for (i, item) in items.enumerate() {
+            let bullet = if ordered {
+                format!("{}.", i + 1)
+            } else {
+                "-".into()
+            };
+            let mut text = Vec::new();
+            let mut nested = Vec::new();
+            for handle in item.children().top().iter() {
+                match handle.get(self.parser).and_then(Node::as_tag) {
+                    Some(child) if matches!(tag_name(child).as_str(), "ul" | "ol") => {
+                        nested.push(self.list(child, depth + 1))
+                    }
+                    _ => text.push(self.inline(std::slice::from_ref(handle))),
+                }
+            }
+            lines.push(format!("{indent}{bullet} {}", text.concat().trim()));
+            lines.extend(nested);
+        }
+        lines.join("\n")
+    }
+
+    fn inline(&self, handles: &[NodeHandle]) -> String {
+        let mut text = String::new();
+        for handle in handles {
+            match handle.get(self.parser) {
+                Some(Node::Raw(raw)) => {
+                    let raw = decode_html_entities(&raw.as_utf8_str());
+                    text.push_str(&collapse_whitespace(&raw).replace('*', r"\*"));
+                }
+                Some(Node::Tag(tag)) => text.push_str(&self.inline_tag(tag)),
+                _ => {}
+            }
+        }
+        text
+    }
+
+    fn inline_tag(&self, tag: &HTMLTag) -> String {
+        let children = tag.children();
+        let content = || self.inline(children.top().as_slice());
+        match tag_name(tag).as_str() {
+            "em" | "strong" | "b" => wrap(&content(), "**"),
+            "i" => wrap(&content(), "_"),
+            "code" => {
+                let code = decode_html_entities(&tag.inner_text(self.parser));
+                let code = wrap(&collapse_whitespace(&code), "`");
+                // Markdown doesn't render emphasis inside of code
+                match self.contains_emphasis(tag) {
+                    true => wrap(&code, "**"),
+                    false => code,
+                }
+            }
+            "a" => {
+                let href = tag
+                    .attributes()
+                    .get("href")
+                    .flatten()
+                    .map(|href| decode_html_entities(&href.as_utf8_str()));
+                match href {
+                    Some(href) => format!("[{}]({href})", content()),
+                    None => content(),
+                }
+            }
+            "br" => "  \n".into(),
+            "script" | "style" => String::new(),
+            _ => content(),
+        }
+    }
+
+    fn contains_emphasis(&self, tag: &HTMLTag) -> bool {
+        tag.children()
+            .all(self.parser)
+            .iter()
+            .filter_map(Node::as_tag)
+            .any(|child| matches!(tag_name(child).as_str(), "em" | "strong" | "b"))
+    }
+}
+
+fn tag_name(tag: &HTMLTag) -> String {
+    tag.name().as_utf8_str().to_lowercase()
+}
+
+/// Wraps text in a Markdown marker, keeping surrounding whitespace outside of it.
+fn wrap(text: &str, marker: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    format!("{leading}{marker}{trimmed}{marker}{trailing}")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut last_was_whitespace = false;
+    for char in text.chars() {
+        if char.is_whitespace() {
+            if !last_was_whitespace {
+                collapsed.push(' ');
+            }
+            last_was_whitespace = true;
+        } else {
+            collapsed.push(char);
+            last_was_whitespace = false;
+        }
+    }
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = r#"<article class="day-desc"><h2>--- Day 1: Calorie   Counting ---</h2>
+<p>The elves carry <em>calories</em>, see <a href="/2022/about">the <span title="Hi">rules</span></a>.</p>
+<pre><code>1000
+&lt;2000&gt;
+
+3000
+</code></pre>
+<ul>
+<li>A total of <code><em>6000</em></code> and <em><code>4000</code></em></li>
+<li>Nested:<ul><li>item</li></ul></li>
+</ul>
+</article>
+<article><h2 id="part2">--- Part Two ---</h2><p>Count   the 2 * 3
+elves.</p></article>"#;
+        let expected = r#"## Day 1: Calorie Counting
+
+The elves carry **calories**, see [the rules](/2022/about).
+
+```
+1000
+<2000>
+
+3000
+```
+
+- A total of **`6000`** and **`4000`**
+- Nested:
+  - item
+
+## Part Two
+
+Count the 2 \* 3 elves.
+"#;
+        assert_eq!(html_to_markdown(html).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_html_output() {
+        let html = r#"<html><body><header><h1>Advent of Code</h1></header>
+<main><article><p>That's the <em>right</em> answer!</p></article>
+<p>You can <a href="/2022">return</a>.</p></main></body></html>"#;
+        assert_eq!(
+            format_html_output(html).unwrap(),
+            "That's the **right** answer!\n"
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
-use clap::{command, Parser, Subcommand};
+use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Parser)]
@@ -8,42 +8,221 @@ use serde::{Deserialize, Serialize};
 pub struct Args {
     #[command(subcommand)]
     pub command: Command,
-    #[arg(short, long, default_value = "./aoc.toml")]
-    pub config: PathBuf,
+    /// Day config to use instead of searching upwards for `aoc.toml` files
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+    /// Print which config files were loaded
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+    /// Base URL of the Advent of Code server, e.g. a local stand-in for testing
+    #[arg(long, global = true)]
+    pub base_url: Option<String>,
+    /// Fetch everything again instead of using cached responses
+    #[arg(long, global = true)]
+    pub refresh: bool,
+    /// Account to use, overrides the `profile` of the config
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+}
+
+/// Options of creating a new day, shared by `new` and `wait`.
+#[derive(Debug, Clone, clap::Args)]
+pub struct NewOptions {
+    pub output: Option<PathBuf>,
+    /// Language of the new day, as registered in `languages.toml` or built in.
+    /// Defaults to the `lang` of the config or rust.
+    #[arg(short, long)]
+    pub lang: Option<String>,
+    /// Overrides the template of the language
+    #[arg(short, long)]
+    pub template: Option<PathBuf>,
+    /// Save all examples and select the most likely one without asking
+    #[arg(long)]
+    pub non_interactive: bool,
+    #[arg(long)]
+    pub example_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Subcommand)]
 pub enum Command {
     New {
-        output: Option<PathBuf>,
         #[arg(short, long)]
         year: Option<u32>,
-        #[arg(short, long)]
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
         day: u32,
-        #[arg(short, long, default_value = "./template")]
-        template: PathBuf,
+        #[command(flatten)]
+        options: NewOptions,
+    },
+    /// Waits until the puzzle unlocks and creates the day like `new`
+    Wait {
+        /// Defaults to the current year
+        #[arg(short, long)]
+        year: Option<u32>,
+        /// Defaults to the next puzzle to unlock
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: Option<u32>,
+        #[command(flatten)]
+        options: NewOptions,
     },
     Download {
         #[arg(short, long)]
         example: bool,
         #[arg(short, long)]
         year: Option<u32>,
-        #[arg(short, long)]
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
         day: Option<u32>,
+        /// Save all examples and select the most likely one without asking
+        #[arg(long, requires = "example")]
+        non_interactive: bool,
+        #[arg(long, requires = "example")]
+        example_index: Option<usize>,
     },
     Submit {
         result: String,
         #[arg(short, long)]
         year: Option<u32>,
-        #[arg(short, long)]
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
         day: Option<u32>,
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=2))]
+        level: Option<u32>,
+        /// Submit even if the answer is known to be wrong
+        #[arg(short, long)]
+        force: bool,
+    },
+    Run {
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=2))]
+        level: Option<u32>,
+        #[arg(short, long, conflicts_with = "input")]
+        example: bool,
         #[arg(short, long)]
+        input: bool,
+        /// Submit the last line of the output as the answer
+        #[arg(short, long, conflicts_with = "example")]
+        submit: bool,
+        #[arg(short, long, requires = "submit")]
+        force: bool,
+    },
+    Check {
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=2))]
         level: Option<u32>,
     },
+    Leaderboard {
+        id: u64,
+        #[arg(short, long)]
+        year: Option<u32>,
+        /// Show the completion times of a single day
+        #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: Option<u32>,
+        /// Show who gained stars since the last fetch
+        #[arg(short, long, conflicts_with = "day")]
+        since: bool,
+    },
+    /// Saves the session cookie of the profile and validates it
+    Login {
+        /// Only validate the saved session
+        #[arg(long)]
+        check: bool,
+    },
+    /// Shows the completion times, ranks and scores of the account with the
+    /// wrong answers of the local ledgers
+    Stats {
+        #[arg(short, long)]
+        year: Option<u32>,
+        /// Directory containing the `day_*` directories
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+        #[arg(short, long, value_enum, default_value_t = StatsFormat::Table)]
+        format: StatsFormat,
+    },
+    /// Saves the puzzles, inputs and examples of every unlocked day of a year
+    Archive {
+        #[arg(short, long)]
+        year: Option<u32>,
+        /// Directory containing a directory per archived year
+        #[arg(short, long, default_value = "./archive")]
+        output: PathBuf,
+    },
+    Status {
+        #[arg(short, long)]
+        year: Option<u32>,
+        /// Directory containing the `day_*` directories
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatsFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+/// Content of an `aoc.toml`.
+///
+/// A repository level file holds defaults like the template and language, the
+/// file of a day its year, day and examples. Files further down the directory
+/// tree override the ones above.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
-    pub year: u32,
-    pub day: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Template directory of `new` for the language `lang`, relative to the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<PathBuf>,
+    /// Language of `new`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// File containing the session cookie, relative to the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_file: Option<PathBuf>,
+    /// Profile whose session is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Index of the `<pre>` block on the puzzle page that was saved as example
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<Example>,
+}
+
+/// A named account, e.g. of a team member.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// File containing the session cookie, relative to the config file
+    pub session_file: PathBuf,
+}
+
+/// Example input with the answer the problem binary has to print for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Example {
+    pub level: u32,
+    #[serde(default = "default_example_file")]
+    pub file: String,
+    pub expected: String,
+    /// Extra arguments passed to the problem binary after the file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+}
+
+fn default_example_file() -> String {
+    "example.txt".into()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_args() {
+        Args::command().debug_assert();
+    }
 }
@@ -0,0 +1,216 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result};
+use regex::Regex;
+use tokio::fs;
+
+use crate::template::matches_pattern;
+
+/// A language directory of the repository that contains the day, e.g. `./rust/day_5`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayLink {
+    pub language: String,
+    pub path: String,
+}
+
+/// Finds the directories `<root>/<language>/day_<day>` of every language.
+pub async fn find_day_links(root: &Path, day: u32) -> Result<Vec<DayLink>> {
+    let mut links = Vec::new();
+    let mut read_dir = fs::read_dir(root)
+        .await
+        .with_context(|| format!("Couldn't read '{}'", root.to_string_lossy()))?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        let dir_name = entry.file_name().to_string_lossy().to_string();
+        if dir_name.starts_with('.') || !entry.path().join(format!("day_{day}")).is_dir() {
+            continue;
+        }
+        links.push(DayLink {
+            language: language_name(&dir_name),
+            path: format!("./{dir_name}/day_{day}"),
+        });
+    }
+    links.sort_by(|a, b| a.language.cmp(&b.language));
+    Ok(links)
+}
+
+/// Inserts or replaces the entry of the day in the day list of the README.
+///
+/// The list is kept sorted by day and the rest of the README is left untouched,
+/// so updating an entry twice gives the same result.
+pub fn update_readme(readme: &str, year: u32, day: u32, links: &[DayLink]) -> String {
+    let day_regex = Regex::new(r"^- \[Day (\d+)\]").unwrap();
+    let lines: Vec<_> = readme.lines().collect();
+    let first = lines.iter().position(|line| day_regex.is_match(line));
+    let (before, list_and_after) = lines.split_at(first.unwrap_or(lines.len()));
+
+    let mut entries: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+    let mut current = None;
+    let mut list_len = 0;
+    for line in list_and_after {
+        if let Some(captures) = day_regex.captures(line) {
+            let entry_day = captures[1].parse().unwrap_or_default();
+            entries.insert(entry_day, vec![line.to_string()]);
+            current = Some(entry_day);
+        } else if let (Some(entry_day), true) = (current, line.starts_with("  ")) {
+            entries.entry(entry_day).or_default().push(line.to_string());
+        } else {
+            break;
+        }
+        list_len += 1;
+    }
+    let after = &list_and_after[list_len..];
+
+    let mut entry = vec![format!(
+        "- [Day {day}](https://adventofcode.com/{year}/day/{day})"
+    )];
+    entry.extend(
+        links
+            .iter()
+            .map(|link| format!("  - [{}]({})", link.language, link.path)),
+    );
+    entries.insert(day, entry);
+
+    let mut result: Vec<String> = before.iter().map(ToString::to_string).collect();
+    if first.is_none() && result.last().is_some_and(|line| !line.is_empty()) {
+        result.push(String::new());
+    }
+    result.extend(entries.into_values().flatten());
+    result.extend(after.iter().map(ToString::to_string));
+    result.join("\n") + "\n"
+}
+
+/// Returns why the Cargo workspace in `language_dir` doesn't include the crate
+/// directory `name`, or `None` if it does or there is no workspace.
+pub async fn check_workspace(language_dir: &Path, name: &str) -> Result<Option<String>> {
+    let path = language_dir.join("Cargo.toml");
+    let Ok(content) = fs::read_to_string(&path).await else {
+        return Ok(None);
+    };
+    let manifest: toml::Value = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse '{}'", path.to_string_lossy()))?;
+    let Some(workspace) = manifest.get("workspace") else {
+        return Ok(None);
+    };
+    let patterns = |key: &str| -> Vec<String> {
+        workspace
+            .get(key)
+            .and_then(|value| value.as_array())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str())
+                    .map(|pattern| {
+                        pattern
+                            .trim_start_matches("./")
+                            .trim_end_matches('/')
+                            .into()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let path_str = path.to_string_lossy();
+    if patterns("exclude")
+        .iter()
+        .any(|pattern| matches_pattern(pattern, name))
+    {
+        return Ok(Some(format!(
+            "'{name}' is excluded from the workspace in '{path_str}'"
+        )));
+    }
+    if !patterns("members")
+        .iter()
+        .any(|pattern| matches_pattern(pattern, name))
+    {
+        return Ok(Some(format!(
+            "'{name}' doesn't match the workspace members in '{path_str}'"
+        )));
+    }
+    Ok(None)
+}
+
+/// The repository root of an absolute day directory like `<root>/rust/day_5`,
+/// if it has a README.
+pub fn find_repo_root(day_dir: &Path) -> Option<PathBuf> {
+    let root = day_dir.parent()?.parent()?;
+    root.join("README.md").is_file().then(|| root.to_path_buf())
+}
+
+fn language_name(dir_name: &str) -> String {
+    match dir_name {
+        "go" => "Go".into(),
+        "js" => "JavaScript".into(),
+        "ts" => "TypeScript".into(),
+        _ => {
+            let mut chars = dir_name.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const README: &str = "# Advent of Code 2022
+
+- [Day 1](https://adventofcode.com/2022/day/1)
+  - [Go](./go/day_1)
+- [Day 3](https://adventofcode.com/2022/day/3)
+  - [Go](./go/day_3)
+
+Footer
+";
+
+    fn links(day: u32) -> Vec<DayLink> {
+        vec![
+            DayLink {
+                language: "Go".into(),
+                path: format!("./go/day_{day}"),
+            },
+            DayLink {
+                language: "Rust".into(),
+                path: format!("./rust/day_{day}"),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_update_readme() {
+        let updated = update_readme(README, 2022, 2, &links(2));
+        assert_eq!(
+            updated,
+            "# Advent of Code 2022
+
+- [Day 1](https://adventofcode.com/2022/day/1)
+  - [Go](./go/day_1)
+- [Day 2](https://adventofcode.com/2022/day/2)
+  - [Go](./go/day_2)
+  - [Rust](./rust/day_2)
+- [Day 3](https://adventofcode.com/2022/day/3)
+  - [Go](./go/day_3)
+
+Footer
+"
+        );
+        assert_eq!(update_readme(&updated, 2022, 2, &links(2)), updated);
+
+        let replaced = update_readme(README, 2022, 3, &links(3));
+        assert!(replaced.contains("  - [Go](./go/day_3)\n  - [Rust](./rust/day_3)\n\nFooter"));
+    }
+
+    #[test]
+    fn test_update_readme_without_list() {
+        let updated = update_readme("# Title\n", 2022, 1, &links(1)[1..]);
+        assert_eq!(
+            updated,
+            "# Title\n\n- [Day 1](https://adventofcode.com/2022/day/1)\n  - [Rust](./rust/day_1)\n"
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use eyre::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::model::Config;
+
+/// Where the session cookie comes from, resolved from the profile and config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionSource {
+    Env,
+    File(PathBuf),
+}
+
+impl SessionSource {
+    /// A selected profile wins over `AOC_SESSION`, which wins over the
+    /// `session_file` of the config.
+    ///
+    /// Profiles without an entry in the config and the default account keep
+    /// their session in `<sessions_dir>/<profile>`.
+    pub fn resolve(config: &Config, profile: Option<&str>, sessions_dir: &Path) -> Self {
+        if let Some(profile) = profile.or(config.profile.as_deref()) {
+            return match config.profiles.get(profile) {
+                Some(profile) => Self::File(profile.session_file.clone()),
+                None => Self::File(sessions_dir.join(profile)),
+            };
+        }
+        if env::var_os("AOC_SESSION").is_some() {
+            return Self::Env;
+        }
+        match &config.session_file {
+            Some(session_file) => Self::File(session_file.clone()),
+            None => Self::File(sessions_dir.join("default")),
+        }
+    }
+
+    pub async fn load(&self) -> Result<String> {
+        let session = match self {
+            Self::Env => env::var("AOC_SESSION").context("AOC_SESSION is not valid unicode")?,
+            Self::File(path) => fs::read_to_string(path).await.with_context(|| {
+                format!(
+                    "No session found in '{}', set AOC_SESSION or run `aoc login`",
+                    path.to_string_lossy()
+                )
+            })?,
+        };
+        let session = session.trim().to_string();
+        if session.is_empty() {
+            bail!("The session cookie is empty");
+        }
+        Ok(session)
+    }
+
+    /// Saves the session to the file, which is only readable by the user.
+    pub async fn save(&self, session: &str) -> Result<&Path> {
+        let Self::File(path) = self else {
+            bail!("The session is taken from AOC_SESSION, unset it to save one");
+        };
+        let path_str = path.to_string_lossy();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        fs::write(path, format!("{}\n", session.trim()))
+            .await
+            .with_context(|| format!("Couldn't write to '{path_str}'"))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).await?;
+        }
+        Ok(path)
+    }
+}
+
+/// Name of the cache directory of the account the session belongs to, which
+/// doesn't reveal the session.
+pub fn account_cache_key(session: &str) -> String {
+    Sha256::digest(session.trim().as_bytes())[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Parses the account name from the header of a page, which is only shown to
+/// logged in users.
+pub fn parse_account_name(html: &str) -> Result<Option<String>> {
+    let dom = tl::parse(html, Default::default())?;
+    let parser = dom.parser();
+    let Some(user) = dom
+        .query_selector("div.user")
+        .unwrap()
+        .find_map(|node| node.get(parser)?.as_tag())
+    else {
+        return Ok(None);
+    };
+    // The name is followed by the star count in a `<span>`
+    let name: String = user
+        .children()
+        .top()
+        .iter()
+        .filter_map(|handle| handle.get(parser)?.as_raw())
+        .map(|raw| raw.as_utf8_str().to_string())
+        .collect();
+    let name = name.trim();
+    Ok((!name.is_empty()).then(|| name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::model::Profile;
+
+    #[test]
+    fn test_resolve_profile() {
+        let config = Config {
+            session_file: Some("./session".into()),
+            profile: Some("alice".into()),
+            profiles: BTreeMap::from([(
+                "bob".into(),
+                Profile {
+                    session_file: "/secrets/bob".into(),
+                },
+            )]),
+            ..Default::default()
+        };
+        let sessions = Path::new("/config/sessions");
+        assert_eq!(
+            SessionSource::resolve(&config, None, sessions),
+            SessionSource::File("/config/sessions/alice".into())
+        );
+        assert_eq!(
+            SessionSource::resolve(&config, Some("bob"), sessions),
+            SessionSource::File("/secrets/bob".into())
+        );
+    }
+
+    #[test]
+    fn test_account_cache_key() {
+        let key = account_cache_key("alice-session");
+        assert_eq!(key.len(), 16);
+        assert_eq!(account_cache_key("alice-session\n"), key);
+        assert_ne!(account_cache_key("bob-session"), key);
+    }
+
+    #[test]
+    fn test_parse_account_name() {
+        let html =
+            r#"<header><div class="user">Alice <span class="star-count">42*</span></div></header>"#;
+        assert_eq!(parse_account_name(html).unwrap(), Some("Alice".into()));
+        assert_eq!(
+            parse_account_name("<main><p>To play, please identify yourself</p></main>").unwrap(),
+            None
+        );
+    }
+}
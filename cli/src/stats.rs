@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use eyre::Result;
+use serde::Serialize;
+
+use crate::{
+    calendar::{format_solve_time, unlock_time},
+    examples::decode_html_entities,
+    ledger::Ledger,
+    submit::SubmitOutcome,
+};
+
+/// Time, rank and score of a part on the personal leaderboard times page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PartStats {
+    /// As shown on the page, like `00:05:12` or `>24h`
+    pub time: String,
+    pub rank: u32,
+    pub score: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DayStats {
+    pub day: u32,
+    pub part_1: Option<PartStats>,
+    pub part_2: Option<PartStats>,
+    /// Wrong answers recorded in the local ledger of the day
+    pub wrong_answers: Option<usize>,
+    /// Time from the unlock to the first correct answer of the local ledger
+    pub first_correct: Option<String>,
+}
+
+/// Parses the days of the personal leaderboard times page, which are listed
+/// from the last day to the first in a `<pre>` block.
+pub fn parse_personal_times(html: &str) -> Result<BTreeMap<u32, DayStats>> {
+    let dom = tl::parse(html, Default::default())?;
+    let parser = dom.parser();
+    let mut days = BTreeMap::new();
+    for node in dom.query_selector("pre").unwrap() {
+        let text = decode_html_entities(&node.get(parser).unwrap().inner_text(parser));
+        for line in text.lines() {
+            let columns: Vec<_> = line.split_whitespace().collect();
+            let (7, Ok(day)) = (columns.len(), columns[0].parse()) else {
+                continue;
+            };
+            days.insert(
+                day,
+                DayStats {
+                    day,
+                    part_1: parse_part(&columns[1..4]),
+                    part_2: parse_part(&columns[4..7]),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    Ok(days)
+}
+
+fn parse_part(columns: &[&str]) -> Option<PartStats> {
+    let [time, rank, score] = columns else {
+        return None;
+    };
+    Some(PartStats {
+        time: time.to_string(),
+        rank: rank.parse().ok()?,
+        score: score.parse().ok()?,
+    })
+}
+
+impl DayStats {
+    /// Adds the wrong answers and the time to the first correct answer of the
+    /// local ledger of the day.
+    pub fn add_ledger(&mut self, year: u32, ledger: &Ledger) {
+        let wrong_answers = ledger
+            .submissions
+            .iter()
+            .filter(|submission| submission.outcome.is_wrong())
+            .count();
+        self.wrong_answers = Some(wrong_answers);
+        self.first_correct = ledger
+            .submissions
+            .iter()
+            .filter(|submission| submission.outcome == SubmitOutcome::Correct)
+            .map(|submission| submission.timestamp)
+            .min()
+            .zip(unlock_time(year, self.day))
+            .map(|(timestamp, unlock)| format_solve_time(timestamp - unlock));
+    }
+}
+
+pub fn print_stats(days: &BTreeMap<u32, DayStats>) {
+    println!(
+        "{:>4}  {:>9} {:>6} {:>5}  {:>9} {:>6} {:>5}  {:>5}  {:>12}",
+        "", "Part 1", "", "", "Part 2", "", "", "Local", ""
+    );
+    println!(
+        "{:>4}  {:>9} {:>6} {:>5}  {:>9} {:>6} {:>5}  {:>5}  {:>12}",
+        "Day", "Time", "Rank", "Score", "Time", "Rank", "Score", "Wrong", "First answer"
+    );
+    for stats in days.values() {
+        let [time_1, rank_1, score_1] = part_columns(&stats.part_1);
+        let [time_2, rank_2, score_2] = part_columns(&stats.part_2);
+        let [wrong, first_correct] = local_columns(stats);
+        println!(
+            "{:>4}  {time_1:>9} {rank_1:>6} {score_1:>5}  {time_2:>9} {rank_2:>6} {score_2:>5}  \
+             {wrong:>5}  {first_correct:>12}",
+            stats.day
+        );
+    }
+}
+
+pub fn to_csv(days: &BTreeMap<u32, DayStats>) -> String {
+    let mut csv = "day,part_1_time,part_1_rank,part_1_score,part_2_time,part_2_rank,\
+                   part_2_score,wrong_answers,first_correct\n"
+        .to_string();
+    for stats in days.values() {
+        let mut columns = vec![stats.day.to_string()];
+        columns.extend(part_columns(&stats.part_1));
+        columns.extend(part_columns(&stats.part_2));
+        columns.extend(local_columns(stats));
+        let columns: Vec<_> = columns
+            .into_iter()
+            .map(|column| if column == "-" { String::new() } else { column })
+            .collect();
+        csv.push_str(&columns.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn part_columns(part: &Option<PartStats>) -> [String; 3] {
+    match part {
+        Some(part) => [
+            part.time.clone(),
+            part.rank.to_string(),
+            part.score.to_string(),
+        ],
+        None => ["-".into(), "-".into(), "-".into()],
+    }
+}
+
+fn local_columns(stats: &DayStats) -> [String; 2] {
+    [
+        stats
+            .wrong_answers
+            .map_or("-".into(), |wrong| wrong.to_string()),
+        stats.first_correct.clone().unwrap_or_else(|| "-".into()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::ledger::Submission;
+
+    const HTML: &str = r#"<main><article><p>These are your personal leaderboard statistics.</p>
+<pre>      <span class="leaderboard-daydesc-first">--------Part 1--------</span>   <span class="leaderboard-daydesc-both">--------Part 2--------</span>
+Day   <span class="leaderboard-daydesc-first">    Time   Rank  Score</span>   <span class="leaderboard-daydesc-both">    Time   Rank  Score</span>
+  2   00:12:01   2345      0          -      -      -
+  1   00:05:12    123      0       &gt;24h  45678      0
+</pre></article></main>"#;
+
+    #[test]
+    fn test_parse_personal_times() {
+        let days = parse_personal_times(HTML).unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(
+            days[&1].part_1,
+            Some(PartStats {
+                time: "00:05:12".into(),
+                rank: 123,
+                score: 0
+            })
+        );
+        assert_eq!(days[&1].part_2.as_ref().unwrap().rank, 45678);
+        assert_eq!(days[&2].part_2, None);
+    }
+
+    #[test]
+    fn test_add_ledger_and_csv() {
+        let mut days = parse_personal_times(HTML).unwrap();
+        let submission = |outcome, minute| Submission {
+            level: 1,
+            answer: "1".into(),
+            outcome,
+            timestamp: Utc.with_ymd_and_hms(2022, 12, 2, 5, minute, 30).unwrap(),
+        };
+        let mut ledger = Ledger::default();
+        ledger.submissions = vec![
+            submission(SubmitOutcome::TooLow, 10),
+            submission(SubmitOutcome::Correct, 12),
+            submission(SubmitOutcome::WrongLevel, 13),
+        ];
+        days.get_mut(&2).unwrap().add_ledger(2022, &ledger);
+        assert_eq!(
+            to_csv(&days),
+            "day,part_1_time,part_1_rank,part_1_score,part_2_time,part_2_rank,part_2_score,\
+             wrong_answers,first_correct
+1,00:05:12,123,0,>24h,45678,0,,
+2,00:12:01,2345,0,,,,1,00:12:30
+"
+        );
+    }
+}
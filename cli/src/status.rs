@@ -0,0 +1,105 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use eyre::{Context, Result};
+use tokio::fs;
+
+use crate::model::Config;
+
+/// A `day_*` directory of the current year.
+#[derive(Debug, Clone)]
+pub struct LocalDay {
+    pub dir: PathBuf,
+    pub has_example: bool,
+    pub has_input: bool,
+}
+
+/// Finds the `day_*` directories in `root` that belong to `year`.
+///
+/// The day is read from their `aoc.toml`, directories without one are matched by name.
+pub async fn find_local_days(root: &Path, year: u32) -> Result<BTreeMap<u32, LocalDay>> {
+    let mut days = BTreeMap::new();
+    let mut read_dir = fs::read_dir(root)
+        .await
+        .with_context(|| format!("Couldn't read '{}'", root.to_string_lossy()))?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(day_from_name) = name.strip_prefix("day_") else {
+            continue;
+        };
+        if !entry.file_type().await?.is_dir() {
+            continue;
+        }
+        let dir = entry.path();
+        let day = match fs::read_to_string(dir.join("aoc.toml")).await {
+            Ok(content) => match toml::from_str::<Config>(&content) {
+                Ok(config) if config.year == Some(year) => config.day,
+                _ => None,
+            },
+            Err(_) => day_from_name.parse().ok(),
+        };
+        if let Some(day) = day {
+            let has_example = fs::metadata(dir.join("example.txt")).await.is_ok();
+            let has_input = fs::metadata(dir.join("input.txt")).await.is_ok();
+            days.insert(
+                day,
+                LocalDay {
+                    dir,
+                    has_example,
+                    has_input,
+                },
+            );
+        }
+    }
+    Ok(days)
+}
+
+pub fn print_status(year: u32, stars: &BTreeMap<u32, u32>, local_days: &BTreeMap<u32, LocalDay>) {
+    let days: BTreeSet<_> = stars.keys().chain(local_days.keys()).collect();
+    println!("{}", format!("Advent of Code {year}").cyan());
+    println!(
+        "{:>4}  {:<5}  {:<24}  {:<7}  {:<5}  Notes",
+        "Day", "Stars", "Directory", "Example", "Input"
+    );
+    for day in days {
+        let day_stars = stars.get(day).copied().unwrap_or(0);
+        let local_day = local_days.get(day);
+        let mut notes = Vec::new();
+        match (day_stars, local_day) {
+            (1.., None) => notes.push("solved but no local crate".yellow()),
+            (0, Some(_)) => notes.push("local crate but unsolved".yellow()),
+            (1, Some(_)) => notes.push("part two unsolved".yellow()),
+            _ => {}
+        }
+        if let Some(local_day) = local_day {
+            if !local_day.has_example {
+                notes.push("missing example".red());
+            }
+            if !local_day.has_input {
+                notes.push("missing input".red());
+            }
+        }
+
+        let star_symbols = match day_stars {
+            2 => "**".yellow(),
+            1 => "* ".white(),
+            _ => "  ".normal(),
+        };
+        let dir = local_day.map_or("-".into(), |day| day.dir.to_string_lossy().to_string());
+        let yes_no = |value: Option<bool>| match value {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "-",
+        };
+        let notes: Vec<_> = notes.iter().map(ToString::to_string).collect();
+        println!(
+            "{day:>4}  {star_symbols:<5}  {dir:<24}  {:<7}  {:<5}  {}",
+            yes_no(local_day.map(|day| day.has_example)),
+            yes_no(local_day.map(|day| day.has_input)),
+            notes.join(", ")
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use std::{fmt::Display, time::Duration};
+
+use colored::{ColoredString, Colorize};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Classified response of the server to a submitted answer.
+///
+/// Serialized as a plain string like `too_high`, the wait of a rate limit is
+/// appended in seconds, e.g. `rate_limited:34`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum SubmitOutcome {
+    Correct,
+    Wrong,
+    TooHigh,
+    TooLow,
+    RateLimited(Duration),
+    AlreadySolved,
+    WrongLevel,
+}
+
+impl SubmitOutcome {
+    /// Classifies the text of a submit response.
+    ///
+    /// The server answers with the same message if the level was already solved
+    /// or is not unlocked yet. Both are classified as [`SubmitOutcome::WrongLevel`],
+    /// the caller has to check the puzzle page to tell them apart.
+    pub fn from_response(response: &str) -> Option<Self> {
+        let response = response.split_whitespace().collect::<Vec<_>>().join(" ");
+        if response.contains("That's the right answer") {
+            return Some(Self::Correct);
+        }
+        if response.contains("not the right answer") {
+            if response.contains("your answer is too high") {
+                return Some(Self::TooHigh);
+            }
+            if response.contains("your answer is too low") {
+                return Some(Self::TooLow);
+            }
+            return Some(Self::Wrong);
+        }
+        if response.contains("You gave an answer too recently") {
+            return Some(Self::RateLimited(parse_wait(&response).unwrap_or_default()));
+        }
+        if response.contains("You don't seem to be solving the right level") {
+            return Some(Self::WrongLevel);
+        }
+        None
+    }
+
+    /// Whether the answer was checked and turned out to be incorrect.
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Self::Wrong | Self::TooHigh | Self::TooLow)
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Correct => 0,
+            Self::Wrong => 2,
+            Self::TooHigh => 3,
+            Self::TooLow => 4,
+            Self::RateLimited(_) => 5,
+            Self::AlreadySolved => 6,
+            Self::WrongLevel => 7,
+        }
+    }
+
+    pub fn summary(&self) -> ColoredString {
+        match self {
+            Self::Correct => "Correct answer!".green(),
+            Self::Wrong => "Wrong answer.".red(),
+            Self::TooHigh => "Wrong answer, it is too high.".red(),
+            Self::TooLow => "Wrong answer, it is too low.".red(),
+            Self::RateLimited(wait) => format!(
+                "Answered too recently, wait {} before trying again.",
+                format_duration(*wait)
+            )
+            .yellow(),
+            Self::AlreadySolved => "This level is already solved.".yellow(),
+            Self::WrongLevel => "This level is not unlocked yet.".yellow(),
+        }
+    }
+}
+
+impl Display for SubmitOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Correct => write!(f, "correct"),
+            Self::Wrong => write!(f, "wrong"),
+            Self::TooHigh => write!(f, "too high"),
+            Self::TooLow => write!(f, "too low"),
+            Self::RateLimited(wait) => write!(f, "rate limited ({})", format_duration(*wait)),
+            Self::AlreadySolved => write!(f, "already solved"),
+            Self::WrongLevel => write!(f, "wrong level"),
+        }
+    }
+}
+
+impl From<SubmitOutcome> for String {
+    fn from(outcome: SubmitOutcome) -> Self {
+        match outcome {
+            SubmitOutcome::Correct => "correct".into(),
+            SubmitOutcome::Wrong => "wrong".into(),
+            SubmitOutcome::TooHigh => "too_high".into(),
+            SubmitOutcome::TooLow => "too_low".into(),
+            SubmitOutcome::RateLimited(wait) => format!("rate_limited:{}", wait.as_secs()),
+            SubmitOutcome::AlreadySolved => "already_solved".into(),
+            SubmitOutcome::WrongLevel => "wrong_level".into(),
+        }
+    }
+}
+
+impl TryFrom<String> for SubmitOutcome {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let outcome = match value.as_str() {
+            "correct" => Self::Correct,
+            "wrong" => Self::Wrong,
+            "too_high" => Self::TooHigh,
+            "too_low" => Self::TooLow,
+            "already_solved" => Self::AlreadySolved,
+            "wrong_level" => Self::WrongLevel,
+            _ => {
+                let wait = value
+                    .strip_prefix("rate_limited:")
+                    .and_then(|seconds| seconds.parse().ok())
+                    .ok_or_else(|| format!("Unknown submit outcome '{value}'"))?;
+                Self::RateLimited(Duration::from_secs(wait))
+            }
+        };
+        Ok(outcome)
+    }
+}
+
+fn parse_wait(response: &str) -> Option<Duration> {
+    let wait_regex = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait").unwrap();
+    let captures = wait_regex.captures(response)?;
+    let minutes: u64 = captures
+        .get(1)
+        .map_or(Ok(0), |minutes| minutes.as_str().parse())
+        .ok()?;
+    let seconds: u64 = captures[2].parse().ok()?;
+    Some(Duration::from_secs(minutes * 60 + seconds))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match (seconds / 60, seconds % 60) {
+        (0, seconds) => format!("{seconds}s"),
+        (minutes, seconds) => format!("{minutes}m {seconds}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_response() {
+        assert_eq!(
+            SubmitOutcome::from_response("That's the right answer! You are one gold star closer."),
+            Some(SubmitOutcome::Correct)
+        );
+        assert_eq!(
+            SubmitOutcome::from_response("That's not the right answer. If you're stuck, ..."),
+            Some(SubmitOutcome::Wrong)
+        );
+        assert_eq!(
+            SubmitOutcome::from_response(
+                "That's not the right answer; your answer is too high. If you're stuck"
+            ),
+            Some(SubmitOutcome::TooHigh)
+        );
+        assert_eq!(
+            SubmitOutcome::from_response("That's not the right\nanswer; your answer is too\nlow."),
+            Some(SubmitOutcome::TooLow)
+        );
+        assert_eq!(
+            SubmitOutcome::from_response(
+                "You gave an answer too recently; ... You have 1m 5s left to wait."
+            ),
+            Some(SubmitOutcome::RateLimited(Duration::from_secs(65)))
+        );
+        assert_eq!(
+            SubmitOutcome::from_response(
+                "You gave an answer too recently; ... You have 34s left to wait."
+            ),
+            Some(SubmitOutcome::RateLimited(Duration::from_secs(34)))
+        );
+        assert_eq!(
+            SubmitOutcome::from_response(
+                "You don't seem to be solving the right level. Did you already complete it?"
+            ),
+            Some(SubmitOutcome::WrongLevel)
+        );
+        assert_eq!(SubmitOutcome::from_response("Something unexpected"), None);
+    }
+}
@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use async_recursion::async_recursion;
+use eyre::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use tokio::fs;
+
+/// Optional metadata in the root of a template, it isn't copied to the new day.
+pub const TEMPLATE_CONFIG_FILE: &str = "aoc-template.toml";
+
+/// Declares which files of a template get their placeholders substituted.
+///
+/// Patterns are matched against the path relative to the template root, `*`
+/// matches within a path segment and `**` across segments. File names are
+/// always substituted.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TemplateConfig {
+    /// Files whose content is templated, all files if empty
+    #[serde(default)]
+    pub templated: Vec<String>,
+    /// Files copied verbatim, even if they match `templated`
+    #[serde(default)]
+    pub verbatim: Vec<String>,
+}
+
+impl TemplateConfig {
+    pub async fn load(template: &Path) -> Result<Self> {
+        let path = template.join(TEMPLATE_CONFIG_FILE);
+        let Ok(content) = fs::read_to_string(&path).await else {
+            return Ok(Self::default());
+        };
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse '{}'", path.to_string_lossy()))
+    }
+
+    fn is_templated(&self, path: &str) -> bool {
+        let templated = self.templated.is_empty()
+            || self
+                .templated
+                .iter()
+                .any(|pattern| matches_pattern(pattern, path));
+        templated
+            && !self
+                .verbatim
+                .iter()
+                .any(|pattern| matches_pattern(pattern, path))
+    }
+}
+
+/// Values of the `{{name}}` placeholders, unknown placeholders are kept as they are.
+#[derive(Debug, Clone)]
+pub struct TemplateVars {
+    values: Vec<(&'static str, String)>,
+}
+
+impl TemplateVars {
+    pub fn new(year: u32, day: u32, title: Option<&str>) -> Self {
+        Self {
+            values: vec![
+                ("year", year.to_string()),
+                ("day", day.to_string()),
+                ("day_padded", format!("{day:02}")),
+                ("title", title.unwrap_or_default().to_string()),
+            ],
+        }
+    }
+
+    pub fn render(&self, text: &str) -> String {
+        self.values
+            .iter()
+            .fold(text.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{{{name}}}}}"), value)
+            })
+    }
+}
+
+/// Parses the title from the `--- Day 1: Calorie Counting ---` heading of a puzzle page.
+pub fn parse_title(html: &str) -> Option<String> {
+    let title_regex = Regex::new(r"--- Day \d+: (.+?) ---").unwrap();
+    let captures = title_regex.captures(html)?;
+    Some(captures[1].trim().to_string())
+}
+
+/// Copies a template to `output`, substituting placeholders as declared in its config.
+pub async fn copy_template(template: &Path, output: &Path, vars: &TemplateVars) -> Result<()> {
+    let config = TemplateConfig::load(template).await?;
+    copy_dir_all(template, output, "", &config, vars).await
+}
+
+#[async_recursion]
+async fn copy_dir_all(
+    src: &Path,
+    dst: &Path,
+    relative: &str,
+    config: &TemplateConfig,
+    vars: &TemplateVars,
+) -> Result<()> {
+    fs::create_dir_all(dst).await?;
+    let mut read_dir = fs::read_dir(src).await?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = format!("{relative}{name}");
+        if path == TEMPLATE_CONFIG_FILE {
+            continue;
+        }
+        let target = dst.join(vars.render(&name));
+        if entry.file_type().await?.is_dir() {
+            copy_dir_all(&entry.path(), &target, &format!("{path}/"), config, vars).await?;
+            continue;
+        }
+        let content = fs::read(entry.path()).await?;
+        match String::from_utf8(content) {
+            Ok(text) if config.is_templated(&path) => fs::write(target, vars.render(&text)).await?,
+            Ok(text) => fs::write(target, text).await?,
+            Err(err) => fs::write(target, err.into_bytes()).await?,
+        }
+    }
+    Ok(())
+}
+
+pub fn matches_pattern(pattern: &str, path: &str) -> bool {
+    let regex = regex::escape(pattern)
+        .replace(r"\*\*", ".*")
+        .replace(r"\*", "[^/]*");
+    Regex::new(&format!("^{regex}$")).is_ok_and(|regex| regex.is_match(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let vars = TemplateVars::new(2022, 5, Some("Supply Stacks"));
+        assert_eq!(
+            vars.render("day_{{day}} {{day_padded}}/{{year}}: {{title}} {{flags}}"),
+            "day_5 05/2022: Supply Stacks {{flags}}"
+        );
+    }
+
+    #[test]
+    fn test_parse_title() {
+        let html = "<article><h2>--- Day 1: Calorie Counting ---</h2><p>...</p></article>";
+        assert_eq!(parse_title(html), Some("Calorie Counting".into()));
+        assert_eq!(parse_title("<h2>--- Part Two ---</h2>"), None);
+    }
+
+    #[test]
+    fn test_is_templated() {
+        let config = TemplateConfig {
+            templated: vec!["src/**".into(), "*.toml".into()],
+            verbatim: vec!["src/**/*.txt".into()],
+        };
+        assert!(config.is_templated("Cargo.toml"));
+        assert!(config.is_templated("src/bin/problem_1.rs"));
+        assert!(!config.is_templated("src/bin/example.txt"));
+        assert!(!config.is_templated("justfile"));
+        assert!(!config.is_templated("nested/Cargo.toml"));
+        assert!(TemplateConfig::default().is_templated("justfile"));
+    }
+}
@@ -0,0 +1,32 @@
+// Day {{day}}: {{title}}
+package main
+
+import (
+	"fmt"
+	"tobias-walle/aoc-22/utils"
+)
+
+func main() {
+	lines, err := utils.ParseInputFileLinesFromArgs()
+	utils.PanicOnErr(err)
+	defer lines.Close()
+
+	result, err := solveProblem(lines)
+	utils.PanicOnErr(err)
+
+	fmt.Println(result)
+}
+
+func solveProblem(lines utils.LineParser) (int, error) {
+	result := 0
+	for {
+		_, done, err := lines.Next()
+		if err != nil {
+			return 0, err
+		}
+		if done {
+			break
+		}
+	}
+	return result, nil
+}
@@ -0,0 +1,830 @@
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    process::{Child, Command, Output, Stdio},
+};
+
+const AOC: &str = env!("CARGO_BIN_EXE_aoc");
+const FAKE_AOC: &str = env!("CARGO_BIN_EXE_fake_aoc");
+
+struct FakeAoc {
+    child: Child,
+    base_url: String,
+}
+
+impl FakeAoc {
+    fn start(args: &[&str]) -> Self {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let mut child = Command::new(FAKE_AOC)
+            .arg("--fixtures")
+            .arg(fixtures)
+            .args(args)
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to start fake server");
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let base_url = line
+            .trim()
+            .strip_prefix("Listening on ")
+            .expect("Unexpected server output")
+            .to_string();
+        Self { child, base_url }
+    }
+}
+
+impl Drop for FakeAoc {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aoc-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn aoc(server: &FakeAoc, dir: &Path, args: &[&str], stdin: &str) -> Output {
+    aoc_at(&server.base_url, dir, args, stdin, Some("test-session"))
+}
+
+fn aoc_at(base_url: &str, dir: &Path, args: &[&str], stdin: &str, session: Option<&str>) -> Output {
+    let mut command = Command::new(AOC);
+    match session {
+        Some(session) => command.env("AOC_SESSION", session),
+        None => command.env_remove("AOC_SESSION"),
+    };
+    let mut child = command
+        .current_dir(dir)
+        .env("XDG_CACHE_HOME", dir.join(".cache"))
+        .env("XDG_CONFIG_HOME", dir.join(".config"))
+        .env("AOC_THROTTLE_MS", "0")
+        .arg("--base-url")
+        .arg(base_url)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Submits an answer to the fake server directly, like on the website.
+fn solve_on_website(server: &FakeAoc, year: u32, day: u32, level: u32, answer: &str) {
+    let address = server.base_url.strip_prefix("http://").unwrap();
+    let body = format!("level={level}&answer={answer}");
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "POST /{year}/day/{day}/answer HTTP/1.1\r\nHost: {address}\r\n\
+         Cookie: session=test-session\r\n\
+         Content-Type: application/x-www-form-urlencoded\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.contains("That's the right answer"), "{response}");
+}
+
+/// Creates a crate whose `problem_1` prints the input file and the answer of the
+/// fixture input.
+fn create_day_crate(dir: &Path, aoc_toml: &str) {
+    fs::create_dir_all(dir.join("src/bin")).unwrap();
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"day_1\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n[workspace]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("src/bin/problem_1.rs"),
+        r#"fn main() { println!("Read {}", std::env::args().nth(1).unwrap()); println!("24000"); }"#,
+    )
+    .unwrap();
+    fs::write(dir.join("aoc.toml"), aoc_toml).unwrap();
+}
+
+#[test]
+fn test_new_download_and_submit() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("end-to-end");
+    let template = dir.join("template");
+    fs::create_dir_all(&template).unwrap();
+    fs::write(
+        template.join("Cargo.toml"),
+        "[package]\nname = \"template\"\n",
+    )
+    .unwrap();
+    fs::write(
+        template.join("notes_{{day_padded}}.md"),
+        "# {{year}} day {{day}}: {{title}}\n",
+    )
+    .unwrap();
+    fs::write(template.join("justfile"), "run:\n  echo {{day}}\n").unwrap();
+    fs::write(
+        template.join("aoc-template.toml"),
+        "verbatim = [\"justfile\"]\n",
+    )
+    .unwrap();
+
+    let output = aoc(
+        &server,
+        &dir,
+        &["new", "-y", "2022", "-d", "1", "-t", "./template"],
+        "0\n",
+    );
+    assert!(output.status.success(), "{output:?}");
+    let day_dir = dir.join("day_1");
+    let cargo_toml = fs::read_to_string(day_dir.join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains(r#"name = "day_1""#));
+    let notes = fs::read_to_string(day_dir.join("notes_01.md")).unwrap();
+    assert_eq!(notes, "# 2022 day 1: Calorie Counting\n");
+    let justfile = fs::read_to_string(day_dir.join("justfile")).unwrap();
+    assert!(justfile.contains("{{day}}"));
+    assert!(!day_dir.join("aoc-template.toml").exists());
+    let problem = fs::read_to_string(day_dir.join("problem.md")).unwrap();
+    assert!(problem.contains("Calorie Counting"));
+    assert!(!problem.contains("Part Two"));
+    let input = fs::read_to_string(day_dir.join("input.txt")).unwrap();
+    assert!(input.starts_with("1000\n2000"));
+    let example = fs::read_to_string(day_dir.join("example.txt")).unwrap();
+    assert!(example.contains("10000"));
+
+    let output = aoc(&server, &day_dir, &["submit", "24000"], "");
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("That's the right answer"));
+    let problem = fs::read_to_string(day_dir.join("problem.md")).unwrap();
+    assert!(problem.contains("Part Two"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_download_uses_cache() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("download-cache");
+    fs::write(dir.join("aoc.toml"), "year = 2022\nday = 1\n").unwrap();
+
+    let output = aoc(&server, &dir, &["download"], "");
+    assert!(output.status.success(), "{output:?}");
+    fs::remove_file(dir.join("input.txt")).unwrap();
+
+    // The puzzle page is fetched again, as levels might be solved on the website
+    solve_on_website(&server, 2022, 1, 1, "24000");
+    let output = aoc(&server, &dir, &["download"], "");
+    assert!(output.status.success(), "{output:?}");
+    let problem = fs::read_to_string(dir.join("problem.md")).unwrap();
+    assert!(problem.contains("Part Two"), "{problem}");
+    assert!(fs::read_to_string(dir.join("input.txt"))
+        .unwrap()
+        .starts_with("1000\n2000"));
+
+    let stopped = server.base_url.clone();
+    drop(server);
+    let output = aoc_at(&stopped, &dir, &["download"], "", Some("test-session"));
+    assert!(!output.status.success(), "{output:?}");
+    let output = aoc_at(
+        &stopped,
+        &dir,
+        &["download", "--refresh"],
+        "",
+        Some("test-session"),
+    );
+    assert!(!output.status.success(), "{output:?}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_new_registers_day() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("registers-day");
+    let readme = "# AoC\n\n- [Day 3](https://adventofcode.com/2022/day/3)\n  - [Go](./go/day_3)\n";
+    fs::write(dir.join("README.md"), readme).unwrap();
+    fs::create_dir_all(dir.join("go/day_1")).unwrap();
+    let rust = dir.join("rust");
+    fs::create_dir_all(rust.join("template")).unwrap();
+    fs::write(
+        rust.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"*/\"]\nexclude = [\"day_2\"]\n",
+    )
+    .unwrap();
+
+    let args = ["new", "-y", "2022", "-d", "1", "--non-interactive"];
+    let output = aoc(&server, &rust, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    let expected = "# AoC
+
+- [Day 1](https://adventofcode.com/2022/day/1)
+  - [Go](./go/day_1)
+  - [Rust](./rust/day_1)
+- [Day 3](https://adventofcode.com/2022/day/3)
+  - [Go](./go/day_3)
+";
+    assert_eq!(fs::read_to_string(dir.join("README.md")).unwrap(), expected);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Warning"));
+
+    let args = ["new", "-y", "2022", "-d", "2", "--non-interactive"];
+    let output = aoc(&server, &rust, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("'day_2' is excluded from the workspace"),
+        "{stdout}"
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_new_languages() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("new-languages");
+
+    let args = [
+        "new",
+        "-y",
+        "2022",
+        "-d",
+        "1",
+        "-l",
+        "go",
+        "--non-interactive",
+    ];
+    let output = aoc(&server, &dir, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    for level in 1..=2 {
+        let main = fs::read_to_string(dir.join(format!("day_1/problem_{level}/main.go"))).unwrap();
+        assert!(main.starts_with("// Day 1: Calorie Counting\npackage main"));
+    }
+
+    fs::create_dir_all(dir.join(".config/aoc/py-template")).unwrap();
+    fs::write(dir.join(".config/aoc/py-template/README.md"), "{{title}}").unwrap();
+    fs::write(
+        dir.join(".config/aoc/languages.toml"),
+        r#"
+[language.python]
+template = "./py-template"
+
+[[language.python.steps]]
+action = "write"
+file = "day_{{day_padded}}.py"
+content = "print('{{title}}')"
+"#,
+    )
+    .unwrap();
+    let args = [
+        "new",
+        "-y",
+        "2022",
+        "-d",
+        "2",
+        "-l",
+        "python",
+        "--non-interactive",
+    ];
+    let output = aoc(&server, &dir, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    let script = fs::read_to_string(dir.join("day_2/day_02.py")).unwrap();
+    assert_eq!(script, "print('Rock Paper Scissors')");
+    let readme = fs::read_to_string(dir.join("day_2/README.md")).unwrap();
+    assert_eq!(readme, "Rock Paper Scissors");
+
+    let args = ["new", "-y", "2022", "-d", "3", "-l", "cobol"];
+    let output = aoc(&server, &dir, &args, "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown language 'cobol'"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_wait_retries_until_available() {
+    let server = FakeAoc::start(&["--locked-requests", "1"]);
+    let dir = temp_dir("wait");
+    fs::create_dir_all(dir.join("template")).unwrap();
+
+    let args = ["wait", "-y", "2022", "-d", "1", "--non-interactive"];
+    let output = aoc(&server, &dir, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Day 1 is unlocked"));
+    assert!(stdout.contains("Puzzle not available yet, retry in 1s"));
+    let problem = fs::read_to_string(dir.join("day_1/problem.md")).unwrap();
+    assert!(problem.contains("Calorie Counting"));
+
+    for day in ["0", "26", "32"] {
+        let output = aoc(&server, &dir, &["wait", "-y", "2022", "-d", day], "");
+        assert_eq!(output.status.code(), Some(2), "{output:?}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("not in 1..=25"));
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_hierarchical_config() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("hierarchical-config");
+    fs::write(
+        dir.join("aoc.toml"),
+        "year = 2022\ntemplate = \"./templates/rust\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(dir.join("templates/rust")).unwrap();
+    fs::write(dir.join("templates/rust/notes.md"), "{{title}}").unwrap();
+    let rust = dir.join("rust");
+    fs::create_dir_all(&rust).unwrap();
+
+    let output = aoc(&server, &rust, &["new", "-d", "1", "--non-interactive"], "");
+    assert!(output.status.success(), "{output:?}");
+    let notes = fs::read_to_string(rust.join("day_1/notes.md")).unwrap();
+    assert_eq!(notes, "Calorie Counting");
+
+    // The template of the config only applies to its language
+    let go = dir.join("go");
+    fs::create_dir_all(&go).unwrap();
+    let args = ["new", "-d", "1", "-l", "go", "--non-interactive"];
+    let output = aoc(&server, &go, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    assert!(go.join("day_1/problem_1/main.go").exists());
+    assert!(!go.join("day_1/notes.md").exists());
+
+    let day_dir = rust.join("day_1");
+    let output = aoc(&server, &day_dir, &["download", "--verbose"], "");
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let repo_config = stdout.find("hierarchical-config").unwrap();
+    let day_config = stdout.find("day_1/aoc.toml").unwrap();
+    assert!(repo_config < day_config, "{stdout}");
+
+    // Commands of a day work from its subdirectories
+    let src_dir = day_dir.join("src");
+    fs::create_dir_all(&src_dir).unwrap();
+    fs::remove_file(day_dir.join("input.txt")).unwrap();
+    let output = aoc(&server, &src_dir, &["download"], "");
+    assert!(output.status.success(), "{output:?}");
+    assert!(day_dir.join("input.txt").exists());
+    let output = aoc(&server, &src_dir, &["submit", "24000"], "");
+    assert!(output.status.success(), "{output:?}");
+    assert!(day_dir.join("submissions.toml").exists());
+    let problem = fs::read_to_string(day_dir.join("problem.md")).unwrap();
+    assert!(problem.contains("Part Two"));
+    assert!(!src_dir.join("problem.md").exists());
+    assert!(!src_dir.join("input.txt").exists());
+
+    fs::write(dir.join("aoc.toml"), "year = ").unwrap();
+    let output = aoc(&server, &day_dir, &["download"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to parse"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_sessions_and_profiles() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("sessions");
+    create_day_crate(
+        &dir,
+        "year = 2022\nday = 1\n\n[[examples]]\nlevel = 1\nexpected = \"24000\"\n",
+    );
+    let base_url = server.base_url.as_str();
+
+    // Offline commands don't need a session
+    let output = aoc_at(base_url, &dir, &["check"], "", None);
+    assert!(output.status.success(), "{output:?}");
+    let output = aoc_at(base_url, &dir, &["download"], "", None);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("run `aoc login`"));
+
+    let output = aoc_at(base_url, &dir, &["login"], "test-session\n", None);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Logged in as Fake User"), "{stdout}");
+    assert!(!stdout.contains("test-session"));
+    let session_file = dir.join(".config/aoc/sessions/default");
+    assert_eq!(fs::read_to_string(session_file).unwrap(), "test-session\n");
+    let output = aoc_at(base_url, &dir, &["login", "--check"], "", None);
+    assert!(output.status.success(), "{output:?}");
+
+    let args = ["login", "--profile", "work"];
+    let output = aoc_at(base_url, &dir, &args, "expired-session\n", None);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid or expired"));
+    let args = ["login", "--check", "--profile", "work"];
+    let output = aoc_at(base_url, &dir, &args, "", Some("test-session"));
+    assert!(
+        !output.status.success(),
+        "the profile wins over AOC_SESSION"
+    );
+
+    // Every account has its own cache
+    for session in ["test-session", "other-session", "test-session"] {
+        let output = aoc_at(base_url, &dir, &["download"], "", Some(session));
+        assert!(output.status.success(), "{output:?}");
+    }
+    let accounts = fs::read_dir(dir.join(".cache/aoc/http")).unwrap().count();
+    assert_eq!(accounts, 2);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_submit_outcomes() {
+    let server = FakeAoc::start(&["--lockout", "0"]);
+    let dir = temp_dir("submit-outcomes");
+    fs::write(dir.join("aoc.toml"), "year = 2022\nday = 1\n").unwrap();
+
+    let output = aoc(&server, &dir, &["submit", "-l", "0", "45000"], "");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not in 1..=2"));
+    assert!(!dir.join("submissions.toml").exists());
+
+    let submit = |args: &[&str]| {
+        let args = [&["submit"], args].concat();
+        aoc(&server, &dir, &args, "").status.code()
+    };
+    assert_eq!(submit(&["-l", "2", "45000"]), Some(7));
+    assert_eq!(submit(&["-l", "1", "1"]), Some(4));
+    assert_eq!(submit(&["-l", "1", "99999"]), Some(3));
+    assert_eq!(submit(&["-l", "1", "abc"]), Some(2));
+
+    // Refused by the ledger, as the answers are known to be wrong
+    assert_eq!(submit(&["-l", "1", "abc"]), Some(1));
+    assert_eq!(submit(&["-l", "1", "0"]), Some(1));
+    assert_eq!(submit(&["-l", "1", "100000"]), Some(1));
+
+    assert_eq!(submit(&["-l", "1", "24000"]), Some(0));
+    assert_eq!(submit(&["-l", "1", "24000"]), Some(1));
+    assert_eq!(submit(&["-l", "1", "--force", "24000"]), Some(6));
+
+    let ledger = fs::read_to_string(dir.join("submissions.toml")).unwrap();
+    assert_eq!(ledger.matches("[[submission]]").count(), 6);
+    assert!(ledger.contains("outcome = 'too_high'"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_submit_rate_limited() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("submit-rate-limited");
+    fs::write(dir.join("aoc.toml"), "year = 2022\nday = 1\n").unwrap();
+
+    let output = aoc(&server, &dir, &["submit", "-l", "1", "99999"], "");
+    assert_eq!(output.status.code(), Some(3), "{output:?}");
+    let output = aoc(&server, &dir, &["submit", "-l", "1", "24000"], "");
+    assert_eq!(output.status.code(), Some(5), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Answered too recently, wait 1m"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_run_and_submit() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("run-and-submit");
+    create_day_crate(&dir, "year = 2022\nday = 1\n");
+    fs::write(dir.join("problem.md"), "--- Day 1: Calorie Counting ---").unwrap();
+
+    let output = aoc(&server, &dir, &["run", "--submit"], "");
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Read ./input.txt"));
+    assert!(stdout.contains("Answer: 24000"));
+    assert!(stdout.contains("That's the right answer"));
+    let ledger = fs::read_to_string(dir.join("submissions.toml")).unwrap();
+    assert!(ledger.contains("outcome = 'correct'"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_submit_prepares_part_two() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("part-two");
+    create_day_crate(&dir, "year = 2022\nday = 2\n");
+
+    let output = aoc(&server, &dir, &["download", "-e", "--non-interactive"], "");
+    assert!(output.status.success(), "{output:?}");
+    // Part One's examples don't come from the cache of the download
+    fs::remove_dir_all(dir.join(".cache")).unwrap();
+    let output = aoc(&server, &dir, &["submit", "-l", "1", "15"], "");
+    assert!(output.status.success(), "{output:?}");
+    let example = fs::read_to_string(dir.join("example_part2.txt")).unwrap();
+    assert_eq!(example, "A Y\nB X\nC Z\nA X\n");
+    assert!(!dir.join("example_part2_2.txt").exists());
+    let problem_1 = fs::read_to_string(dir.join("src/bin/problem_1.rs")).unwrap();
+    let problem_2 = fs::read_to_string(dir.join("src/bin/problem_2.rs")).unwrap();
+    assert_eq!(problem_2, problem_1);
+
+    // Solving Part Two doesn't touch the solution again
+    fs::write(dir.join("src/bin/problem_2.rs"), "// Part Two").unwrap();
+    let output = aoc(&server, &dir, &["submit", "-l", "2", "12"], "");
+    assert!(output.status.success(), "{output:?}");
+    let problem_2 = fs::read_to_string(dir.join("src/bin/problem_2.rs")).unwrap();
+    assert_eq!(problem_2, "// Part Two");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_check_examples() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("check-examples");
+    create_day_crate(
+        &dir,
+        r#"
+year = 2022
+day = 1
+
+[[examples]]
+level = 1
+expected = "24000"
+
+[[examples]]
+level = 1
+file = "other-example.txt"
+expected = "12345"
+args = ["10"]
+"#,
+    );
+
+    let output = aoc(&server, &dir, &["check"], "");
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("PASS Level 1 with 'example.txt'"));
+    assert!(stdout.contains("FAIL Level 1 with 'other-example.txt'"));
+    assert!(stdout.contains("- expected: 12345"));
+    assert!(stdout.contains("+ actual:   24000"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_non_interactive_examples() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("non-interactive-examples");
+    let template = dir.join("template");
+    fs::create_dir_all(&template).unwrap();
+
+    let args = [
+        "new",
+        "-y",
+        "2022",
+        "-d",
+        "1",
+        "-t",
+        "./template",
+        "--non-interactive",
+    ];
+    let output = aoc(&server, &dir, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    let day_dir = dir.join("day_1");
+    let example_0 = fs::read_to_string(day_dir.join("example_0.txt")).unwrap();
+    let example_1 = fs::read_to_string(day_dir.join("example_1.txt")).unwrap();
+    assert_eq!(
+        fs::read_to_string(day_dir.join("example.txt")).unwrap(),
+        example_0
+    );
+    let config = fs::read_to_string(day_dir.join("aoc.toml")).unwrap();
+    assert!(config.contains("example_index = 0"));
+
+    let args = ["download", "--example", "--example-index", "1"];
+    let output = aoc(&server, &day_dir, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(day_dir.join("example.txt")).unwrap(),
+        example_1
+    );
+    let config = fs::read_to_string(day_dir.join("aoc.toml")).unwrap();
+    assert!(config.contains("example_index = 1"));
+
+    // Without the config of a day the index isn't recorded
+    let other_dir = dir.join("other");
+    fs::create_dir_all(&other_dir).unwrap();
+    let args = [
+        "download",
+        "-e",
+        "-y",
+        "2022",
+        "-d",
+        "1",
+        "--example-index",
+        "0",
+    ];
+    let output = aoc(&server, &other_dir, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    assert!(other_dir.join("example.txt").exists());
+    assert!(!other_dir.join("aoc.toml").exists());
+    fs::write(dir.join("aoc.toml"), "year = 2022\n").unwrap();
+    let args = [
+        "--config",
+        "../aoc.toml",
+        "download",
+        "-e",
+        "-d",
+        "1",
+        "--example-index",
+        "0",
+    ];
+    let output = aoc(&server, &other_dir, &args, "");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(dir.join("aoc.toml")).unwrap(),
+        "year = 2022\n"
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_leaderboard() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("leaderboard");
+
+    let output = aoc(&server, &dir, &["leaderboard", "1234", "-y", "2022"], "");
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let alice = stdout.find("Alice").unwrap();
+    let bob = stdout.find("Bob").unwrap();
+    assert!(alice < bob);
+    assert!(stdout.contains("(anonymous user #3)"));
+
+    let output = aoc(
+        &server,
+        &dir,
+        &["leaderboard", "1234", "-y", "2022", "-d", "1"],
+        "",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Using the leaderboard fetched at"));
+    assert!(stdout.contains("00:05:00     00:10:00  Alice"));
+    assert!(stdout.contains("00:16:40            -  Bob"));
+
+    // Pretend an older fetch without Alice's stars on day 2
+    let cache = dir.join(".cache/aoc/leaderboard_2022_1234.json");
+    let cached = fs::read_to_string(&cache).unwrap();
+    let older = cached
+        .replace(r#","2":{"1":{"get_star_ts":1669960923}}"#, "")
+        .replace(r#""fetched_at":"20"#, r#""fetched_at":"19"#);
+    assert_ne!(older, cached);
+    fs::write(&cache, older).unwrap();
+
+    let output = aoc(
+        &server,
+        &dir,
+        &["leaderboard", "1234", "-y", "2022", "--since"],
+        "",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Alice +1 (day 2 part 1)"), "{stdout}");
+    assert!(!stdout.contains("Bob"));
+
+    // The cache is fresh again, so there is nothing to compare
+    let output = aoc(
+        &server,
+        &dir,
+        &["leaderboard", "1234", "-y", "2022", "--since"],
+        "",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No new data was fetched"), "{stdout}");
+    assert!(!stdout.contains("Nobody gained stars"), "{stdout}");
+
+    let args = ["leaderboard", "1234", "-y", "2022", "-d", "40"];
+    let output = aoc(&server, &dir, &args, "");
+    assert_eq!(output.status.code(), Some(2), "{output:?}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_status() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("status");
+    let day_dir = dir.join("day_1");
+    fs::create_dir_all(&day_dir).unwrap();
+    fs::write(day_dir.join("aoc.toml"), "year = 2022\nday = 1\n").unwrap();
+    fs::write(day_dir.join("input.txt"), "1000\n").unwrap();
+
+    let args = ["submit", "-y", "2022", "-d", "2", "-l", "1", "15"];
+    let output = aoc(&server, &dir, &args, "");
+    assert!(output.status.success(), "{output:?}");
+
+    let output = aoc(&server, &dir, &["status", "-y", "2022"], "");
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let day_1 = stdout
+        .lines()
+        .find(|line| line.trim().starts_with("1 "))
+        .unwrap();
+    assert!(day_1.contains("local crate but unsolved"), "{stdout}");
+    assert!(day_1.contains("missing example"), "{stdout}");
+    assert!(!day_1.contains("missing input"), "{stdout}");
+    let day_2 = stdout
+        .lines()
+        .find(|line| line.trim().starts_with("2 "))
+        .unwrap();
+    assert!(day_2.contains("solved but no local crate"), "{stdout}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_archive() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("archive");
+    let archive = |expected: &[&str]| {
+        let output = aoc(&server, &dir, &["archive", "-y", "2022"], "");
+        assert!(output.status.success(), "{output:?}");
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        for expected in expected {
+            assert!(stdout.contains(expected), "{stdout}");
+        }
+    };
+    let year_dir = dir.join("archive/2022");
+
+    archive(&["Saving"]);
+    let input = fs::read_to_string(year_dir.join("day_01/input.txt")).unwrap();
+    assert!(input.starts_with("1000\n2000"));
+    let html = fs::read_to_string(year_dir.join("day_01/puzzle.html")).unwrap();
+    assert!(html.starts_with("<article"));
+    let example = fs::read_to_string(year_dir.join("day_02/example_0.txt")).unwrap();
+    assert_eq!(example, "A Y\nB X\nC Z\n");
+    let index = fs::read_to_string(year_dir.join("index.md")).unwrap();
+    assert!(index.contains("- [Day 1: Calorie Counting](./day_01/problem.md)"));
+    let manifest = fs::read_to_string(year_dir.join("manifest.toml")).unwrap();
+    assert!(manifest.starts_with("version = 1\n"));
+
+    archive(&["Day 1 is already archived", "Day 2 is already archived"]);
+
+    fs::write(year_dir.join("day_01/input.txt"), "changed").unwrap();
+    archive(&["Day 1: input.txt don't match", "Day 2 is already archived"]);
+    assert_eq!(
+        fs::read_to_string(year_dir.join("day_01/input.txt")).unwrap(),
+        input
+    );
+
+    // Part Two is archived once it is unlocked
+    let args = ["submit", "-y", "2022", "-d", "1", "-l", "1", "24000"];
+    assert!(aoc(&server, &dir, &args, "").status.success());
+    archive(&["Day 2 is already archived"]);
+    let problem = fs::read_to_string(year_dir.join("day_01/problem.md")).unwrap();
+    assert!(problem.contains("Part Two"));
+
+    // Stars gained on the website don't archive the cached page
+    solve_on_website(&server, 2022, 2, 1, "15");
+    archive(&["Day 1 is already archived"]);
+    let problem = fs::read_to_string(year_dir.join("day_02/problem.md")).unwrap();
+    assert!(problem.contains("Part Two"), "{problem}");
+    let manifest = fs::read_to_string(year_dir.join("manifest.toml")).unwrap();
+    assert_eq!(manifest.matches("stars = 1").count(), 2, "{manifest}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_stats() {
+    let server = FakeAoc::start(&[]);
+    let dir = temp_dir("stats");
+    let day_dir = dir.join("day_2");
+    fs::create_dir_all(&day_dir).unwrap();
+    fs::write(day_dir.join("aoc.toml"), "year = 2022\nday = 2\n").unwrap();
+    for answer in ["1", "15"] {
+        let output = aoc(&server, &day_dir, &["submit", "-l", "1", answer], "");
+        assert!(output.status.code() != Some(1), "{output:?}");
+    }
+
+    let output = aoc(&server, &dir, &["stats", "-y", "2022"], "");
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("00:05:12"), "{stdout}");
+
+    let output = aoc(&server, &dir, &["stats", "-y", "2022", "-f", "csv"], "");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines[1], "1,00:05:12,123,0,>24h,45678,0,,");
+    assert!(lines[2].starts_with("2,00:12:01,2345,0,,,,1,"), "{stdout}");
+
+    let output = aoc(&server, &dir, &["stats", "-y", "2022", "-f", "json"], "");
+    let days: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(days[0]["part_2"]["rank"], 45678);
+    assert_eq!(days[1]["wrong_answers"], 1);
+
+    fs::remove_dir_all(dir).unwrap();
+}
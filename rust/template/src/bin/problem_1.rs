@@ -1,3 +1,5 @@
+//! Day {{day}}: {{title}}
+
 use eyre::{Context, Result};
 use utils::read_input_file_as_string;
 
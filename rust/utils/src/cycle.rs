@@ -0,0 +1,160 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Repetition in the states of a simulation, found by [`find_cycle`].
+///
+/// Steps count the calls of the step function, step 0 is the initial state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// First step whose state repeats
+    pub start: usize,
+    pub length: usize,
+    /// Measure of the state at every step up to `start + length`
+    pub measures: Vec<i64>,
+}
+
+impl Cycle {
+    /// Change of the measure during one repetition, like the height a tower grows.
+    pub fn delta(&self) -> i64 {
+        self.measures[self.start + self.length] - self.measures[self.start]
+    }
+
+    /// Step before the first repetition ends whose state equals the one at `step`.
+    pub fn equivalent_step(&self, step: usize) -> usize {
+        if step < self.start + self.length {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        }
+    }
+
+    /// Measure at `step`, extrapolated by the delta of the skipped repetitions.
+    pub fn measure_at(&self, step: usize) -> i64 {
+        if step < self.measures.len() {
+            return self.measures[step];
+        }
+        let repetitions = ((step - self.start) / self.length) as i64;
+        self.measures[self.equivalent_step(step)] + repetitions * self.delta()
+    }
+}
+
+/// Runs the simulation until the fingerprint of a state repeats.
+///
+/// The fingerprint has to capture everything the following steps depend on,
+/// e.g. the index of the next piece, the jet and the shape of the top rows of
+/// day 17, while the measure is what keeps changing, like the height.
+/// Returns `None` if no state repeats within `max_steps`.
+pub fn find_cycle<S, K: Hash + Eq>(
+    initial: S,
+    step: impl FnMut(&mut S),
+    fingerprint: impl FnMut(&S) -> K,
+    measure: impl FnMut(&S) -> i64,
+    max_steps: usize,
+) -> Option<Cycle> {
+    match run(initial, step, fingerprint, measure, max_steps) {
+        Run::Cycle(cycle) => Some(cycle),
+        Run::Finished(_) => None,
+    }
+}
+
+/// Measure after `target` steps, skipping ahead once the states repeat.
+///
+/// This answers questions like the height after 10^12 steps, without a cycle
+/// it simulates every step.
+pub fn measure_after<S, K: Hash + Eq>(
+    initial: S,
+    target: usize,
+    step: impl FnMut(&mut S),
+    fingerprint: impl FnMut(&S) -> K,
+    measure: impl FnMut(&S) -> i64,
+) -> i64 {
+    match run(initial, step, fingerprint, measure, target) {
+        Run::Cycle(cycle) => cycle.measure_at(target),
+        Run::Finished(measure) => measure,
+    }
+}
+
+enum Run {
+    Cycle(Cycle),
+    /// Measure of the last step, no state repeated
+    Finished(i64),
+}
+
+fn run<S, K: Hash + Eq>(
+    mut state: S,
+    mut step: impl FnMut(&mut S),
+    mut fingerprint: impl FnMut(&S) -> K,
+    mut measure: impl FnMut(&S) -> i64,
+    max_steps: usize,
+) -> Run {
+    let mut seen = HashMap::new();
+    let mut measures = Vec::new();
+    for current in 0..=max_steps {
+        measures.push(measure(&state));
+        if let Some(start) = seen.insert(fingerprint(&state), current) {
+            return Run::Cycle(Cycle {
+                start,
+                length: current - start,
+                measures,
+            });
+        }
+        if current < max_steps {
+            step(&mut state);
+        }
+    }
+    Run::Finished(*measures.last().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A value that runs into a cycle of length 6 after the first step, with
+    /// the sum of all values as measure.
+    #[derive(Debug, Clone)]
+    struct State {
+        value: i64,
+        sum: i64,
+    }
+
+    fn step(state: &mut State) {
+        state.value = (state.value * state.value + 1) % 10;
+        state.sum += state.value;
+    }
+
+    fn brute_force(steps: usize) -> i64 {
+        let mut state = State { value: 3, sum: 3 };
+        for _ in 0..steps {
+            step(&mut state);
+        }
+        state.sum
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let state = State { value: 3, sum: 3 };
+        let cycle = find_cycle(state, step, |state| state.value, |state| state.sum, 100).unwrap();
+        assert_eq!((cycle.start, cycle.length), (1, 6));
+        assert_eq!(cycle.equivalent_step(20), 2);
+        for target in [0, 5, 8, 9, 100, 12345] {
+            assert_eq!(cycle.measure_at(target), brute_force(target), "{target}");
+        }
+
+        let state = State { value: 3, sum: 3 };
+        assert_eq!(
+            find_cycle(state, step, |state| state.value, |state| state.sum, 6),
+            None
+        );
+    }
+
+    #[test]
+    fn test_measure_after() {
+        let measure = |target| {
+            let state = State { value: 3, sum: 3 };
+            measure_after(state, target, step, |state| state.value, |state| state.sum)
+        };
+        assert_eq!(measure(3), brute_force(3));
+        assert_eq!(measure(1_000_003), brute_force(1_000_003));
+        let delta = measure(1_000_000_000_006) - measure(1_000_000_000_000);
+        assert_eq!(delta, brute_force(7) - brute_force(1));
+    }
+}
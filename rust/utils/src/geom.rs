@@ -0,0 +1,509 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Number type of the coordinates of points.
+pub trait Coord:
+    Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ONE: Self;
+
+    fn abs_diff(self, other: Self) -> Self {
+        if self > other {
+            self - other
+        } else {
+            other - self
+        }
+    }
+}
+
+macro_rules! impl_coord {
+    ($($type:ty),*) => {
+        $(impl Coord for $type {
+            const ONE: Self = 1;
+        })*
+    };
+}
+
+impl_coord!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Coord> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    /// Sum of the distances along the axes, also known as taxicab distance.
+    pub fn manhattan(self, other: Self) -> T {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Largest distance along an axis, the number of king moves between the points.
+    pub fn chebyshev(self, other: Self) -> T {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// The neighbouring point in the direction, `y` grows downwards.
+    ///
+    /// Steps below zero overflow for unsigned coordinates.
+    pub fn step(self, direction: impl Into<Direction8>) -> Self {
+        let (dx, dy) = direction.into().offset();
+        Self::new(shift(self.x, dx), shift(self.y, dy))
+    }
+
+    pub fn neighbours4(self) -> impl Iterator<Item = Self> {
+        Direction4::ALL
+            .into_iter()
+            .map(move |direction| self.step(direction))
+    }
+
+    pub fn neighbours8(self) -> impl Iterator<Item = Self> {
+        Direction8::ALL
+            .into_iter()
+            .map(move |direction| self.step(direction))
+    }
+}
+
+impl<T> Point2<T> {
+    pub fn map<U>(self, mut map: impl FnMut(T) -> U) -> Point2<U> {
+        Point2 {
+            x: map(self.x),
+            y: map(self.y),
+        }
+    }
+
+    /// Converts the coordinates, e.g. from the `usize` indices of a parsed map
+    /// to signed ones.
+    pub fn try_map<U: TryFrom<T>>(self) -> Result<Point2<U>, U::Error> {
+        Ok(Point2 {
+            x: self.x.try_into()?,
+            y: self.y.try_into()?,
+        })
+    }
+}
+
+impl<T: Coord> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn manhattan(self, other: Self) -> T {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        self.x
+            .abs_diff(other.x)
+            .max(self.y.abs_diff(other.y))
+            .max(self.z.abs_diff(other.z))
+    }
+
+    pub fn get(self, axis: Axis) -> T {
+        match axis {
+            Axis::X => self.x,
+            Axis::Y => self.y,
+            Axis::Z => self.z,
+        }
+    }
+
+    /// The point moved by `amount` along the axis.
+    pub fn offset(mut self, axis: Axis, amount: T) -> Self {
+        match axis {
+            Axis::X => self.x = self.x + amount,
+            Axis::Y => self.y = self.y + amount,
+            Axis::Z => self.z = self.z + amount,
+        }
+        self
+    }
+
+    /// The 6 points sharing a face with this one, like adjacent cubes.
+    pub fn neighbours6(self) -> impl Iterator<Item = Self> {
+        Axis::ALL.into_iter().flat_map(move |axis| {
+            let mut below = self;
+            match axis {
+                Axis::X => below.x = below.x - T::ONE,
+                Axis::Y => below.y = below.y - T::ONE,
+                Axis::Z => below.z = below.z - T::ONE,
+            }
+            [below, self.offset(axis, T::ONE)]
+        })
+    }
+}
+
+impl<T> Point3<T> {
+    pub fn map<U>(self, mut map: impl FnMut(T) -> U) -> Point3<U> {
+        Point3 {
+            x: map(self.x),
+            y: map(self.y),
+            z: map(self.z),
+        }
+    }
+}
+
+fn shift<T: Coord>(value: T, delta: i8) -> T {
+    match delta {
+        1 => value + T::ONE,
+        -1 => value - T::ONE,
+        _ => value,
+    }
+}
+
+macro_rules! impl_ops {
+    ($point:ident { $($field:ident),* }) => {
+        impl<T: Coord> Add for $point<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($field: self.$field + other.$field),* }
+            }
+        }
+
+        impl<T: Coord> Sub for $point<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($field: self.$field - other.$field),* }
+            }
+        }
+
+        /// Scales the point by a factor.
+        impl<T: Coord> Mul<T> for $point<T> {
+            type Output = Self;
+
+            fn mul(self, factor: T) -> Self {
+                Self { $($field: self.$field * factor),* }
+            }
+        }
+
+        impl<T: Coord + Neg<Output = T>> Neg for $point<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),* }
+            }
+        }
+
+        impl<T: Coord> AddAssign for $point<T> {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl<T: Coord> SubAssign for $point<T> {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+    };
+}
+
+impl_ops!(Point2 { x, y });
+impl_ops!(Point3 { x, y, z });
+
+impl<T> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<Point2<T>> for (T, T) {
+    fn from(point: Point2<T>) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T> From<[T; 3]> for Point3<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T> From<Point3<T>> for (T, T, T) {
+    fn from(point: Point3<T>) -> Self {
+        (point.x, point.y, point.z)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+/// Direction on a map whose `y` grows downwards, so `Up` decreases `y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction4 {
+    /// Clockwise, starting with `Up`.
+    pub const ALL: [Direction4; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn turn(self, turn: Turn) -> Self {
+        match turn {
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+}
+
+/// The 4 directions of [`Direction4`] and the diagonals between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    /// Clockwise, starting with `Up`.
+    pub const ALL: [Direction8; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    /// Turns by 45°.
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    /// Turns by 45°.
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    /// Change of `x` and `y` of a step in this direction.
+    pub fn offset(self) -> (i8, i8) {
+        match self {
+            Self::Up => (0, -1),
+            Self::UpRight => (1, -1),
+            Self::Right => (1, 0),
+            Self::DownRight => (1, 1),
+            Self::Down => (0, 1),
+            Self::DownLeft => (-1, 1),
+            Self::Left => (-1, 0),
+            Self::UpLeft => (-1, -1),
+        }
+    }
+}
+
+impl From<Direction4> for Direction8 {
+    fn from(direction: Direction4) -> Self {
+        Self::ALL[direction as usize * 2]
+    }
+}
+
+/// Inclusive box around points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoundingBox<P> {
+    pub min: P,
+    pub max: P,
+}
+
+impl<P> BoundingBox<P> {
+    pub const fn new(min: P, max: P) -> Self {
+        Self { min, max }
+    }
+}
+
+impl<T: Coord> BoundingBox<Point2<T>> {
+    /// Box of a `width` x `height` map starting at zero, both have to be at least 1.
+    pub fn from_size(width: T, height: T) -> Self {
+        assert!(
+            width >= T::ONE && height >= T::ONE,
+            "Bounding box must not be empty"
+        );
+        Self::new(
+            Point2::default(),
+            Point2::new(width - T::ONE, height - T::ONE),
+        )
+    }
+
+    /// The smallest box containing all points, if there are any.
+    pub fn containing(points: impl IntoIterator<Item = Point2<T>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |bounds, point| {
+            bounds.extend(point)
+        }))
+    }
+
+    pub fn extend(self, point: Point2<T>) -> Self {
+        Self {
+            min: Point2::new(self.min.x.min(point.x), self.min.y.min(point.y)),
+            max: Point2::new(self.max.x.max(point.x), self.max.y.max(point.y)),
+        }
+    }
+
+    pub fn contains(&self, point: Point2<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::ONE
+    }
+
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::ONE
+    }
+}
+
+impl BoundingBox<Point2<i64>> {
+    /// All points, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Point2<i64>> {
+        let Self { min, max } = *self;
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Point2::new(x, y)))
+    }
+}
+
+impl<T: Coord> BoundingBox<Point3<T>> {
+    /// The smallest box containing all points, if there are any.
+    pub fn containing(points: impl IntoIterator<Item = Point3<T>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |bounds, point| {
+            bounds.extend(point)
+        }))
+    }
+
+    pub fn extend(self, point: Point3<T>) -> Self {
+        Self {
+            min: Point3::new(
+                self.min.x.min(point.x),
+                self.min.y.min(point.y),
+                self.min.z.min(point.z),
+            ),
+            max: Point3::new(
+                self.max.x.max(point.x),
+                self.max.y.max(point.y),
+                self.max.z.max(point.z),
+            ),
+        }
+    }
+
+    pub fn contains(&self, point: Point3<T>) -> bool {
+        Axis::ALL
+            .into_iter()
+            .all(|axis| (self.min.get(axis)..=self.max.get(axis)).contains(&point.get(axis)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_ops_and_distances() {
+        let a = Point2::new(1, -2);
+        let b = Point2::new(-3, 4);
+        assert_eq!(a + b, Point2::new(-2, 2));
+        assert_eq!(a - b, Point2::new(4, -6));
+        assert_eq!(a * 3, Point2::new(3, -6));
+        assert_eq!(-a, Point2::new(-1, 2));
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(a.chebyshev(b), 6);
+        assert_eq!(Point2::new(2usize, 5).manhattan(Point2::new(4, 1)), 6);
+
+        let c = Point3::new(1, 2, 3);
+        assert_eq!(c.manhattan(Point3::new(0, 0, 0)), 6);
+        assert_eq!(c.offset(Axis::Y, -2), Point3::new(1, 0, 3));
+        assert_eq!(c.neighbours6().count(), 6);
+        assert!(c.neighbours6().all(|neighbour| neighbour.manhattan(c) == 1));
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(Direction4::Up.turn_left(), Direction4::Left);
+        assert_eq!(Direction4::Left.turn(Turn::Right), Direction4::Up);
+        assert_eq!(Direction4::Down.opposite(), Direction4::Up);
+        assert_eq!(Direction8::Up.turn_left(), Direction8::UpLeft);
+        assert_eq!(Direction8::from(Direction4::Down), Direction8::Down);
+
+        let point = Point2::new(5usize, 5);
+        assert_eq!(point.step(Direction4::Up), Point2::new(5, 4));
+        assert_eq!(point.step(Direction8::DownLeft), Point2::new(4, 6));
+        let neighbours: Vec<_> = point.neighbours8().collect();
+        assert_eq!(neighbours.len(), 8);
+        assert_eq!(neighbours[..2], [Point2::new(5, 4), Point2::new(6, 4)]);
+    }
+
+    #[test]
+    fn test_bounding_box_and_conversions() {
+        let points = [Point2::new(3, -1), Point2::new(-2, 4), Point2::new(0, 0)];
+        let bounds = BoundingBox::<Point2<i64>>::containing(points).unwrap();
+        assert_eq!(bounds.min, Point2::new(-2, -1));
+        assert_eq!((bounds.width(), bounds.height()), (6, 6));
+        assert!(bounds.contains(Point2::new(3, 4)));
+        assert!(!bounds.contains(Point2::new(3, 5)));
+
+        assert_eq!(
+            BoundingBox::from_size(2, 3),
+            BoundingBox::new(Point2::new(0, 0), Point2::new(1, 2))
+        );
+        assert_eq!(BoundingBox::from_size(2, 1).positions().count(), 2);
+        assert_eq!(
+            Point2::new(1usize, 2).try_map::<i64>(),
+            Ok(Point2::new(1, 2))
+        );
+        assert!(Point2::new(-1i64, 2).try_map::<usize>().is_err());
+        assert_eq!(Point3::from([1, 2, 3]), Point3::new(1, 2, 3));
+    }
+}
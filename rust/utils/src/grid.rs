@@ -0,0 +1,488 @@
+use std::{collections::HashMap, iter};
+
+use eyre::{Context, Result};
+
+use crate::geom::{BoundingBox, Point2};
+
+/// Position in a grid, `y` grows downwards like the lines of the input.
+pub type Pos = Point2<i64>;
+
+/// Inclusive rectangle of positions.
+pub type Bounds = BoundingBox<Pos>;
+
+#[derive(Debug, Clone)]
+enum Cells<T> {
+    /// Every position of the area has a slot, for grids that are mostly filled
+    Dense {
+        area: Bounds,
+        cells: Vec<Option<T>>,
+    },
+    Sparse(HashMap<Pos, T>),
+}
+
+/// A 2D map of cells, like the maps of days 12, 14, 17 and 22.
+///
+/// The dense backend keeps a slot for every position of a rectangle and grows
+/// it when cells are inserted outside, the sparse one stores only the cells
+/// that exist. Both track the bounds of the inserted cells, removing cells
+/// doesn't shrink them.
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+    cells: Cells<T>,
+    bounds: Option<Bounds>,
+    len: usize,
+}
+
+/// Grids are equal if they have the same cells and bounds, whatever their backend.
+impl<T: PartialEq> PartialEq for Grid<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bounds == other.bounds
+            && self.len == other.len
+            && self.iter().all(|(pos, cell)| other.get(pos) == Some(cell))
+    }
+}
+
+impl<T: Eq> Eq for Grid<T> {}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Self::sparse()
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn sparse() -> Self {
+        Self {
+            cells: Cells::Sparse(HashMap::new()),
+            bounds: None,
+            len: 0,
+        }
+    }
+
+    /// An empty dense grid with room for the cells within `area`.
+    pub fn dense(area: Bounds) -> Self {
+        let cells = iter::repeat_with(|| None)
+            .take(area.width() as usize * area.height() as usize)
+            .collect();
+        Self {
+            cells: Cells::Dense { area, cells },
+            bounds: None,
+            len: 0,
+        }
+    }
+
+    /// A dense grid of the rows, starting at `(0, 0)`.
+    pub fn from_rows<R: IntoIterator<Item = T>>(rows: impl IntoIterator<Item = R>) -> Self {
+        let mut grid = Self::sparse();
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, cell) in row.into_iter().enumerate() {
+                grid.insert(Pos::new(x as i64, y as i64), cell);
+            }
+        }
+        grid.into_dense()
+    }
+
+    /// Parses a dense grid with a cell for every character of the lines.
+    ///
+    /// Errors name the line and column of the character that couldn't be parsed.
+    pub fn parse<E>(input: &str, mut parse_cell: impl FnMut(char) -> Result<T, E>) -> Result<Self>
+    where
+        E: Into<eyre::Report>,
+    {
+        Self::parse_sparse(input, |char| parse_cell(char).map(Some)).map(Self::into_dense)
+    }
+
+    /// Parses a sparse grid of the characters for which `parse_cell` returns a
+    /// cell, e.g. to skip the `.` of empty positions.
+    pub fn parse_sparse<E>(
+        input: &str,
+        mut parse_cell: impl FnMut(char) -> Result<Option<T>, E>,
+    ) -> Result<Self>
+    where
+        E: Into<eyre::Report>,
+    {
+        let mut grid = Self::sparse();
+        for (y, line) in input.lines().enumerate() {
+            for (x, char) in line.chars().enumerate() {
+                let cell = parse_cell(char).map_err(Into::into).wrap_err_with(|| {
+                    format!("Invalid cell '{char}' in line {}, column {}", y + 1, x + 1)
+                })?;
+                if let Some(cell) = cell {
+                    grid.insert(Pos::new(x as i64, y as i64), cell);
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    pub fn into_dense(self) -> Self {
+        if matches!(self.cells, Cells::Dense { .. }) {
+            return self;
+        }
+        let mut dense = match self.bounds {
+            Some(bounds) => Self::dense(bounds),
+            None => return self,
+        };
+        for (pos, cell) in self.into_iter() {
+            dense.insert(pos, cell);
+        }
+        dense
+    }
+
+    pub fn into_sparse(self) -> Self {
+        let bounds = self.bounds;
+        let mut sparse = Self::sparse();
+        for (pos, cell) in self.into_iter() {
+            sparse.insert(pos, cell);
+        }
+        sparse.bounds = bounds;
+        sparse
+    }
+
+    /// Bounds of all inserted cells, `None` until the first insert.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        match &self.cells {
+            Cells::Dense { area, cells } => cells[index(area, pos)?].as_ref(),
+            Cells::Sparse(cells) => cells.get(&pos),
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        match &mut self.cells {
+            Cells::Dense { area, cells } => cells[index(area, pos)?].as_mut(),
+            Cells::Sparse(cells) => cells.get_mut(&pos),
+        }
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.get(pos).is_some()
+    }
+
+    /// Sets the cell at `pos` and returns the previous one.
+    pub fn insert(&mut self, pos: Pos, cell: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.extend(pos),
+            None => Bounds::new(pos, pos),
+        });
+        if let Cells::Dense { area, .. } = &self.cells {
+            if !area.contains(pos) {
+                self.grow(grown_area(*area, pos));
+            }
+        }
+        let previous = match &mut self.cells {
+            Cells::Dense { area, cells } => {
+                let index = index(area, pos).unwrap();
+                cells[index].replace(cell)
+            }
+            Cells::Sparse(cells) => cells.insert(pos, cell),
+        };
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, pos: Pos) -> Option<T> {
+        let removed = match &mut self.cells {
+            Cells::Dense { area, cells } => cells[index(area, pos)?].take(),
+            Cells::Sparse(cells) => cells.remove(&pos),
+        };
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// All cells, row by row for dense grids and in arbitrary order for sparse ones.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (Pos, &T)> + '_> {
+        match &self.cells {
+            Cells::Dense { area, cells } => Box::new(
+                area.positions()
+                    .zip(cells)
+                    .filter_map(|(pos, cell)| Some((pos, cell.as_ref()?))),
+            ),
+            Cells::Sparse(cells) => Box::new(cells.iter().map(|(pos, cell)| (*pos, cell))),
+        }
+    }
+
+    /// The existing cells of the 4 neighbours of `pos`, clockwise from above.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = (Pos, &T)> {
+        pos.neighbours4()
+            .filter_map(|pos| Some((pos, self.get(pos)?)))
+    }
+
+    /// The existing cells of the 8 neighbours of `pos`, clockwise from above.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = (Pos, &T)> {
+        pos.neighbours8()
+            .filter_map(|pos| Some((pos, self.get(pos)?)))
+    }
+
+    /// The cells of row `y` within the bounds, `None` where there is no cell.
+    pub fn row(&self, y: i64) -> impl Iterator<Item = Option<&T>> {
+        let (min_x, max_x) = match self.bounds {
+            Some(bounds) => (bounds.min.x, bounds.max.x),
+            None => (0, -1),
+        };
+        (min_x..=max_x).map(move |x| self.get(Pos::new(x, y)))
+    }
+
+    /// The cells of column `x` within the bounds, `None` where there is no cell.
+    pub fn column(&self, x: i64) -> impl Iterator<Item = Option<&T>> {
+        let (min_y, max_y) = match self.bounds {
+            Some(bounds) => (bounds.min.y, bounds.max.y),
+            None => (0, -1),
+        };
+        (min_y..=max_y).map(move |y| self.get(Pos::new(x, y)))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Option<&T>>> {
+        let (min_y, max_y) = self
+            .bounds
+            .map_or((0, -1), |bounds| (bounds.min.y, bounds.max.y));
+        (min_y..=max_y).map(|y| self.row(y))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = Option<&T>>> {
+        let (min_x, max_x) = self
+            .bounds
+            .map_or((0, -1), |bounds| (bounds.min.x, bounds.max.x));
+        (min_x..=max_x).map(|x| self.column(x))
+    }
+
+    /// Mirrors the grid along the diagonal through the top left of the bounds.
+    pub fn transpose(self) -> Self {
+        self.map_positions(|min, _, pos| Pos::new(min.x + (pos.y - min.y), min.y + (pos.x - min.x)))
+    }
+
+    /// Rotates the grid by 90° clockwise, keeping the top left of the bounds.
+    pub fn rotate_right(self) -> Self {
+        self.map_positions(|min, max, pos| {
+            Pos::new(min.x + (max.y - pos.y), min.y + (pos.x - min.x))
+        })
+    }
+
+    /// Rotates the grid by 90° counterclockwise, keeping the top left of the bounds.
+    pub fn rotate_left(self) -> Self {
+        self.map_positions(|min, max, pos| {
+            Pos::new(min.x + (pos.y - min.y), min.y + (max.x - pos.x))
+        })
+    }
+
+    /// Renders the rows within the bounds, e.g. to print the grid while debugging.
+    pub fn render(&self, mut render_cell: impl FnMut(Option<&T>) -> char) -> String {
+        self.rows()
+            .map(|row| row.map(&mut render_cell).collect::<String>() + "\n")
+            .collect()
+    }
+
+    fn map_positions(self, map: impl Fn(Pos, Pos, Pos) -> Pos) -> Self {
+        let Some(bounds) = self.bounds else {
+            return self;
+        };
+        let is_dense = matches!(self.cells, Cells::Dense { .. });
+        let mut mapped = Self::sparse();
+        for (pos, cell) in self.into_iter() {
+            mapped.insert(map(bounds.min, bounds.max, pos), cell);
+        }
+        if is_dense {
+            mapped.into_dense()
+        } else {
+            mapped
+        }
+    }
+
+    fn grow(&mut self, new_area: Bounds) {
+        let Cells::Dense { area, cells } = &mut self.cells else {
+            return;
+        };
+        let mut grown: Vec<_> = iter::repeat_with(|| None)
+            .take(new_area.width() as usize * new_area.height() as usize)
+            .collect();
+        for (pos, cell) in area.positions().zip(cells.drain(..)) {
+            grown[index(&new_area, pos).unwrap()] = cell;
+        }
+        *area = new_area;
+        *cells = grown;
+    }
+}
+
+/// Index of the slot of `pos` in the cells of a dense grid, row by row.
+fn index(area: &Bounds, pos: Pos) -> Option<usize> {
+    area.contains(pos).then(|| {
+        (pos.y - area.min.y) as usize * area.width() as usize + (pos.x - area.min.x) as usize
+    })
+}
+
+/// The area extended to `pos`, at least doubling its extent in the direction
+/// of growth, so that grids growing row by row are reallocated rarely.
+fn grown_area(area: Bounds, pos: Pos) -> Bounds {
+    let (width, height) = (area.width(), area.height());
+    let mut grown = area;
+    if pos.x < area.min.x {
+        grown.min.x = pos.x.min(area.min.x - width);
+    }
+    if pos.x > area.max.x {
+        grown.max.x = pos.x.max(area.max.x + width);
+    }
+    if pos.y < area.min.y {
+        grown.min.y = pos.y.min(area.min.y - height);
+    }
+    if pos.y > area.max.y {
+        grown.max.y = pos.y.max(area.max.y + height);
+    }
+    grown
+}
+
+impl<T> IntoIterator for Grid<T> {
+    type Item = (Pos, T);
+    type IntoIter = std::vec::IntoIter<(Pos, T)>;
+
+    /// All cells, row by row for dense grids and in arbitrary order for sparse ones.
+    fn into_iter(self) -> Self::IntoIter {
+        let cells: Vec<_> = match self.cells {
+            Cells::Dense { area, cells } => area
+                .positions()
+                .zip(cells)
+                .filter_map(|(pos, cell)| Some((pos, cell?)))
+                .collect(),
+            Cells::Sparse(cells) => cells.into_iter().collect(),
+        };
+        cells.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::bail;
+
+    use super::*;
+
+    const INPUT: &str = "ab\ncd\nef\n";
+
+    fn parse(input: &str) -> Grid<char> {
+        Grid::parse(input, Ok::<_, eyre::Report>).unwrap()
+    }
+
+    fn render(grid: &Grid<char>) -> String {
+        grid.render(|cell| cell.copied().unwrap_or('.'))
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = parse(INPUT);
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid.bounds(), Some(Bounds::from_size(2, 3)));
+        assert_eq!(grid.get(Pos::new(1, 2)), Some(&'f'));
+        assert_eq!(grid.get(Pos::new(2, 0)), None);
+
+        let err = Grid::parse(INPUT, |char| match char {
+            'e' => bail!("no e"),
+            char => Ok(char),
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid cell 'e' in line 3, column 1");
+
+        let sparse = Grid::parse_sparse("#.\n.#\n", |char| {
+            Ok::<_, eyre::Report>((char == '#').then_some(()))
+        })
+        .unwrap();
+        assert_eq!(sparse.len(), 2);
+        assert!(!sparse.contains(Pos::new(1, 0)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = parse(INPUT);
+        let neighbours: Vec<_> = grid.neighbours4(Pos::new(0, 1)).map(|(_, c)| *c).collect();
+        assert_eq!(neighbours, vec!['a', 'd', 'e']);
+        let neighbours: Vec<_> = grid.neighbours8(Pos::new(0, 1)).map(|(_, c)| *c).collect();
+        assert_eq!(neighbours, vec!['a', 'b', 'd', 'f', 'e']);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = parse(INPUT);
+        let columns: Vec<String> = grid
+            .columns()
+            .map(|column| column.flatten().collect())
+            .collect();
+        assert_eq!(columns, vec!["ace", "bdf"]);
+        let row: String = grid.row(1).flatten().collect();
+        assert_eq!(row, "cd");
+    }
+
+    #[test]
+    fn test_sparse_bounds_and_growing() {
+        for mut grid in [Grid::sparse(), Grid::dense(Bounds::from_size(1, 1))] {
+            grid.insert(Pos::new(2, -1), '#');
+            grid.insert(Pos::new(-1, 1), '#');
+            assert_eq!(
+                grid.bounds(),
+                Some(Bounds::new(Pos::new(-1, -1), Pos::new(2, 1)))
+            );
+            assert_eq!(render(&grid), "...#\n....\n#...\n");
+            assert_eq!(grid.remove(Pos::new(2, -1)), Some('#'));
+            assert_eq!(grid.len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_dense_grows_geometrically() {
+        let mut grid = Grid::dense(Bounds::from_size(7, 1));
+        let mut areas = Vec::new();
+        for y in 0..1000 {
+            for x in 0..7 {
+                grid.insert(Pos::new(x, -y), y);
+            }
+            if let Cells::Dense { area, .. } = &grid.cells {
+                if areas.last() != Some(area) {
+                    areas.push(*area);
+                }
+            }
+        }
+        assert_eq!(areas.len(), 11);
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(Pos::new(0, -999), Pos::new(6, 0)))
+        );
+        assert_eq!(grid.len(), 7000);
+        assert_eq!(grid.get(Pos::new(3, -500)), Some(&500));
+        assert_eq!(grid.row(-999).flatten().count(), 7);
+    }
+
+    #[test]
+    fn test_equality() {
+        let mut grown = Grid::dense(Bounds::from_size(1, 1));
+        for x in 0..3 {
+            grown.insert(Pos::new(x, 0), 'x');
+        }
+        let from_rows = Grid::from_rows([['x', 'x', 'x']]);
+        assert_eq!(grown, from_rows);
+        assert_eq!(grown.clone().into_sparse(), from_rows);
+        grown.insert(Pos::new(1, 0), 'y');
+        assert_ne!(grown, from_rows);
+        grown.remove(Pos::new(1, 0));
+        assert_ne!(grown, from_rows);
+    }
+
+    #[test]
+    fn test_transpose_and_rotate() {
+        assert_eq!(render(&parse(INPUT).transpose()), "ace\nbdf\n");
+        assert_eq!(render(&parse(INPUT).rotate_right()), "eca\nfdb\n");
+        assert_eq!(render(&parse(INPUT).rotate_left()), "bdf\nace\n");
+        let grid = parse(INPUT).into_sparse().rotate_right().rotate_left();
+        assert_eq!(render(&grid), "ab\ncd\nef\n");
+    }
+}
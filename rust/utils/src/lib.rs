@@ -6,7 +6,11 @@ use std::{
 
 use eyre::{Context, ContextCompat, Result};
 
+pub mod cycle;
+pub mod geom;
+pub mod grid;
 pub mod parsing;
+pub mod search;
 
 pub type Lines = io::Lines<io::BufReader<File>>;
 
@@ -5,24 +5,202 @@ use nom::{
     bytes::complete::tag,
     character::complete::digit1,
     combinator::{map_res, opt, recognize},
+    error::{Error, FromExternalError, ParseError, VerboseError, VerboseErrorKind},
     sequence::tuple,
     IResult,
 };
 
-pub fn number<T: FromStr>(input: &str) -> IResult<&str, T> {
+/// Result of parsers that name their parts with `nom::error::context`, for
+/// [`parse_with_nom`] to report where in the structure parsing failed.
+pub type VerboseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+pub fn number<'a, T, E>(input: &'a str) -> IResult<&'a str, T, E>
+where
+    T: FromStr,
+    E: ParseError<&'a str> + FromExternalError<&'a str, T::Err>,
+{
     map_res(recognize(tuple((opt(tag("-")), digit1))), T::from_str)(input)
 }
 
-pub fn parse_with_nom<P, T>(input: &str, parse: P) -> Result<T>
+/// Nom errors that know where in the input they happened.
+pub trait PositionedError<'a> {
+    /// Describes the error, `input` is what the failed parser was given.
+    fn describe(&self, input: &'a str) -> String;
+}
+
+impl<'a> PositionedError<'a> for Error<&'a str> {
+    fn describe(&self, input: &'a str) -> String {
+        let position = Position::of(input, self.input);
+        format!("{position} ({:?}):\n{}", self.code, position.snippet())
+    }
+}
+
+impl<'a> PositionedError<'a> for VerboseError<&'a str> {
+    /// Reports the innermost error with the stack of contexts it happened in.
+    fn describe(&self, input: &'a str) -> String {
+        let Some((rest, kind)) = self.errors.first() else {
+            return "unknown error".into();
+        };
+        let position = Position::of(input, rest);
+        let expected = match kind {
+            VerboseErrorKind::Context(context) => context.to_string(),
+            VerboseErrorKind::Char(c) => format!("expected '{c}'"),
+            VerboseErrorKind::Nom(kind) => format!("{kind:?}"),
+        };
+        let mut description = format!("{position} ({expected}):\n{}", position.snippet());
+        let contexts: Vec<_> = self
+            .errors
+            .iter()
+            .filter_map(|(rest, kind)| match kind {
+                VerboseErrorKind::Context(context) => Some((context, Position::of(input, rest))),
+                _ => None,
+            })
+            .collect();
+        if !contexts.is_empty() {
+            description.push_str("\nwhile parsing:");
+            for (context, position) in contexts {
+                description.push_str(&format!("\n  {context} at {position}"));
+            }
+        }
+        description
+    }
+}
+
+/// Runs `parse` on the whole input, unconsumed trailing input is ignored.
+///
+/// Errors point at the line and column where parsing failed, parsers returning
+/// a [`VerboseResult`] also list the contexts they failed in.
+pub fn parse_with_nom<'a, P, T, E>(input: &'a str, parse: P) -> Result<T>
 where
-    P: FnOnce(&str) -> IResult<&str, T>,
+    P: FnOnce(&'a str) -> IResult<&'a str, T, E>,
     T: Debug,
+    E: PositionedError<'a>,
 {
-    let (_, parsed) = match parse(input) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            bail!("Failed to parse input: {err}")
+    let (_, parsed) = run(input, parse)?;
+    Ok(parsed)
+}
+
+/// Like [`parse_with_nom`], but fails if anything but whitespace is left over.
+pub fn parse_all_with_nom<'a, P, T, E>(input: &'a str, parse: P) -> Result<T>
+where
+    P: FnOnce(&'a str) -> IResult<&'a str, T, E>,
+    T: Debug,
+    E: PositionedError<'a>,
+{
+    let (rest, parsed) = run(input, parse)?;
+    if !rest.trim().is_empty() {
+        let position = Position::of(input, rest);
+        bail!("Unexpected input at {position}:\n{}", position.snippet());
+    }
+    Ok(parsed)
+}
+
+fn run<'a, P, T, E>(input: &'a str, parse: P) -> Result<(&'a str, T)>
+where
+    P: FnOnce(&'a str) -> IResult<&'a str, T, E>,
+    E: PositionedError<'a>,
+{
+    match parse(input) {
+        Ok(parsed) => Ok(parsed),
+        Err(nom::Err::Error(err) | nom::Err::Failure(err)) => {
+            bail!("Failed to parse input at {}", err.describe(input))
+        }
+        Err(nom::Err::Incomplete(_)) => bail!("Failed to parse input: incomplete input"),
+    }
+}
+
+/// Line and column of a position in the input, both starting at 1.
+struct Position<'a> {
+    line: usize,
+    column: usize,
+    text: &'a str,
+}
+
+impl<'a> Position<'a> {
+    /// Position where `rest`, which nom slices off the end of `input`, starts.
+    fn of(input: &'a str, rest: &str) -> Self {
+        let mut offset = input.len().saturating_sub(rest.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: input[line_start..].lines().next().unwrap_or(""),
         }
+    }
+
+    /// The line of the position with a caret under the column.
+    fn snippet(&self) -> String {
+        let gutter = " ".repeat(self.line.to_string().len());
+        let caret = format!("{}^", " ".repeat(self.column - 1));
+        format!("{} | {}\n{gutter} | {caret}", self.line, self.text)
+    }
+}
+
+impl std::fmt::Display for Position<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{
+        character::complete::{char, line_ending},
+        combinator::cut,
+        error::context,
+        multi::separated_list1,
+        sequence::separated_pair,
     };
-    Ok(parsed)
+
+    use super::*;
+
+    fn pairs(input: &str) -> IResult<&str, Vec<(u32, u32)>> {
+        separated_list1(line_ending, separated_pair(number, char(','), number))(input)
+    }
+
+    fn verbose_pairs(input: &str) -> VerboseResult<'_, Vec<(u32, u32)>> {
+        let pair = context(
+            "pair",
+            separated_pair(context("left", number), char(','), context("right", number)),
+        );
+        context("pairs", separated_list1(line_ending, cut(pair)))(input)
+    }
+
+    #[test]
+    fn test_parse_with_nom() {
+        assert_eq!(
+            parse_with_nom("1,2\n3,4\n", pairs).unwrap(),
+            vec![(1, 2), (3, 4)]
+        );
+        let err = parse_with_nom("x,2", pairs).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse input at line 1, column 1 (Digit):\n1 | x,2\n  | ^"
+        );
+    }
+
+    #[test]
+    fn test_parse_all_with_nom() {
+        assert_eq!(parse_all_with_nom("1,2\n", pairs).unwrap(), vec![(1, 2)]);
+        let err = parse_all_with_nom("1,2\n3,4\n5;6\n", pairs).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected input at line 2, column 4:\n2 | 3,4\n  |    ^"
+        );
+    }
+
+    #[test]
+    fn test_verbose_context_stack() {
+        let err = parse_with_nom("1,2\n3,x", verbose_pairs);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "Failed to parse input at line 2, column 3 (Digit):\n2 | 3,x\n  |   ^\n\
+             while parsing:\n  right at line 2, column 3\n  pair at line 2, column 1\n  \
+             pairs at line 1, column 1"
+        );
+    }
 }
@@ -0,0 +1,291 @@
+use std::{
+    cell::Cell,
+    cmp::Reverse,
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    marker::PhantomData,
+    ops::Add,
+};
+
+use priority_queue::PriorityQueue;
+
+/// Cost of edges and paths, `Default` has to be zero.
+pub trait Cost: Copy + Ord + Default + Add<Output = Self> {}
+
+impl<T: Copy + Ord + Default + Add<Output = T>> Cost for T {}
+
+/// A graph that is explored from its nodes, e.g. the positions of a map.
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+    type Cost: Cost;
+
+    /// The nodes reachable from `node` with the cost of the edge to them.
+    fn neighbours(&self, node: &Self::Node) -> impl IntoIterator<Item = (Self::Node, Self::Cost)>;
+
+    /// Called whenever a search expands a node, e.g. to count them.
+    fn on_expand(&self, _node: &Self::Node) {}
+}
+
+/// Graph of a closure returning the neighbours of a node.
+pub struct FnGraph<N, C, F> {
+    neighbours: F,
+    _marker: PhantomData<fn(&N) -> C>,
+}
+
+pub fn from_fn<N, C, I, F>(neighbours: F) -> FnGraph<N, C, F>
+where
+    F: Fn(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    FnGraph {
+        neighbours,
+        _marker: PhantomData,
+    }
+}
+
+impl<N, C, I, F> Graph for FnGraph<N, C, F>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    F: Fn(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    type Node = N;
+    type Cost = C;
+
+    fn neighbours(&self, node: &N) -> impl IntoIterator<Item = (N, C)> {
+        (self.neighbours)(node)
+    }
+}
+
+/// Counts the nodes the searches on the wrapped graph expand.
+pub struct Counted<G> {
+    graph: G,
+    expanded: Cell<usize>,
+}
+
+impl<G> Counted<G> {
+    pub fn new(graph: G) -> Self {
+        Self {
+            graph,
+            expanded: Cell::new(0),
+        }
+    }
+
+    pub fn expanded(&self) -> usize {
+        self.expanded.get()
+    }
+}
+
+impl<G: Graph> Graph for Counted<G> {
+    type Node = G::Node;
+    type Cost = G::Cost;
+
+    fn neighbours(&self, node: &G::Node) -> impl IntoIterator<Item = (G::Node, G::Cost)> {
+        self.graph.neighbours(node)
+    }
+
+    fn on_expand(&self, node: &G::Node) {
+        self.expanded.set(self.expanded.get() + 1);
+        self.graph.on_expand(node);
+    }
+}
+
+/// Distances of the reached nodes and the node each was reached from.
+#[derive(Debug, Clone)]
+pub struct SearchResult<N, C> {
+    pub distances: HashMap<N, C>,
+    pub came_from: HashMap<N, N>,
+}
+
+impl<N: Clone + Eq + Hash, C: Copy> SearchResult<N, C> {
+    fn new() -> Self {
+        Self {
+            distances: HashMap::new(),
+            came_from: HashMap::new(),
+        }
+    }
+
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.distances.get(node).copied()
+    }
+
+    /// The nodes from a start to `target`, including both.
+    pub fn path_to(&self, target: &N) -> Option<Vec<N>> {
+        self.distances.get(target)?;
+        let mut path = vec![target.clone()];
+        while let Some(previous) = self.came_from.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth-first search counting the edges from `start`, ignoring their costs.
+pub fn bfs<G: Graph>(graph: &G, start: G::Node) -> SearchResult<G::Node, usize> {
+    bfs_multi(graph, [start])
+}
+
+/// Breadth-first search from the nearest of several starts.
+pub fn bfs_multi<G: Graph>(
+    graph: &G,
+    starts: impl IntoIterator<Item = G::Node>,
+) -> SearchResult<G::Node, usize> {
+    let mut result = SearchResult::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        result.distances.insert(start.clone(), 0);
+        queue.push_back(start);
+    }
+    while let Some(node) = queue.pop_front() {
+        graph.on_expand(&node);
+        let distance = result.distances[&node];
+        for (neighbour, _) in graph.neighbours(&node) {
+            if result.distances.contains_key(&neighbour) {
+                continue;
+            }
+            result.distances.insert(neighbour.clone(), distance + 1);
+            result.came_from.insert(neighbour.clone(), node.clone());
+            queue.push_back(neighbour);
+        }
+    }
+    result
+}
+
+/// Cheapest paths from `start` to every reachable node.
+pub fn dijkstra<G: Graph>(graph: &G, start: G::Node) -> SearchResult<G::Node, G::Cost> {
+    best_first(graph, start, None, |_| G::Cost::default())
+}
+
+/// Cheapest path from `start` to `target`, expanding the nodes that look the
+/// most promising by the `heuristic` first.
+///
+/// The heuristic must never overestimate the remaining cost, the search stops
+/// once `target` is reached, so the result only holds the explored nodes.
+pub fn astar<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    target: &G::Node,
+    heuristic: impl Fn(&G::Node) -> G::Cost,
+) -> SearchResult<G::Node, G::Cost> {
+    best_first(graph, start, Some(target), heuristic)
+}
+
+/// Cheapest paths between all pairs of `nodes`, by the start node.
+pub fn all_pairs<G: Graph>(
+    graph: &G,
+    nodes: impl IntoIterator<Item = G::Node>,
+) -> HashMap<G::Node, SearchResult<G::Node, G::Cost>> {
+    nodes
+        .into_iter()
+        .map(|node| (node.clone(), dijkstra(graph, node)))
+        .collect()
+}
+
+fn best_first<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    target: Option<&G::Node>,
+    heuristic: impl Fn(&G::Node) -> G::Cost,
+) -> SearchResult<G::Node, G::Cost> {
+    let mut result = SearchResult::new();
+    let mut queue = PriorityQueue::new();
+    result.distances.insert(start.clone(), G::Cost::default());
+    queue.push(start.clone(), Reverse(heuristic(&start)));
+    while let Some((node, _)) = queue.pop() {
+        if target == Some(&node) {
+            break;
+        }
+        graph.on_expand(&node);
+        let cost = result.distances[&node];
+        for (neighbour, edge_cost) in graph.neighbours(&node) {
+            let tentative_cost = cost + edge_cost;
+            if result
+                .distances
+                .get(&neighbour)
+                .is_some_and(|known| *known <= tentative_cost)
+            {
+                continue;
+            }
+            result.distances.insert(neighbour.clone(), tentative_cost);
+            result.came_from.insert(neighbour.clone(), node.clone());
+            let priority = tentative_cost + heuristic(&neighbour);
+            queue.push_increase(neighbour, Reverse(priority));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Grid, Pos};
+
+    const MAZE: &str = "\
+S..#....
+.#.#.##.
+.#...#..
+.####.#.
+......#E
+";
+
+    fn maze() -> Grid<char> {
+        Grid::parse(MAZE, Ok::<_, eyre::Report>).unwrap()
+    }
+
+    fn maze_graph(maze: &Grid<char>) -> impl Graph<Node = Pos, Cost = u32> + '_ {
+        from_fn(move |pos: &Pos| {
+            maze.neighbours4(*pos)
+                .filter(|(_, cell)| **cell != '#')
+                .map(|(pos, _)| (pos, 1))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn test_bfs_and_path() {
+        let maze = maze();
+        let result = bfs(&maze_graph(&maze), Pos::new(0, 0));
+        let target = Pos::new(7, 4);
+        assert_eq!(result.distance(&target), Some(15));
+        let path = result.path_to(&target).unwrap();
+        assert_eq!(path.len(), 16);
+        assert_eq!(path.first(), Some(&Pos::new(0, 0)));
+        assert!(path.windows(2).all(|step| step[0].manhattan(step[1]) == 1));
+        assert_eq!(result.path_to(&Pos::new(3, 0)), None);
+
+        let result = bfs_multi(&maze_graph(&maze), [Pos::new(0, 0), target]);
+        assert_eq!(result.distance(&Pos::new(7, 0)), Some(4));
+    }
+
+    #[test]
+    fn test_dijkstra_and_astar() {
+        let maze = maze();
+        let target = Pos::new(7, 4);
+        let dijkstra_graph = Counted::new(maze_graph(&maze));
+        let dijkstra_result = dijkstra(&dijkstra_graph, Pos::new(0, 0));
+        let astar_graph = Counted::new(maze_graph(&maze));
+        let astar_result = astar(&astar_graph, Pos::new(0, 0), &target, |pos| {
+            pos.manhattan(target) as u32
+        });
+        assert_eq!(dijkstra_result.distance(&target), Some(15));
+        assert_eq!(astar_result.distance(&target), Some(15));
+        assert_eq!(astar_result.path_to(&target).unwrap().len(), 16);
+        assert!(astar_graph.expanded() < dijkstra_graph.expanded());
+    }
+
+    #[test]
+    fn test_weighted_all_pairs() {
+        let edges = HashMap::from([
+            ('a', vec![('b', 7), ('c', 2)]),
+            ('b', vec![('a', 7), ('c', 3)]),
+            ('c', vec![('a', 2), ('b', 3)]),
+        ]);
+        let graph = from_fn(|node: &char| edges[node].clone());
+        let distances = all_pairs(&graph, ['a', 'b', 'c']);
+        assert_eq!(distances[&'a'].distance(&'b'), Some(5));
+        assert_eq!(distances[&'a'].path_to(&'b'), Some(vec!['a', 'c', 'b']));
+        assert_eq!(distances[&'b'].distance(&'b'), Some(0));
+    }
+}